@@ -4,7 +4,8 @@ use syn::{Expr, Ident, parse_macro_input};
 
 use crate::structs::{EventRequest, FailureRequest};
 
-/// Emits an event carrying an input range.
+/// Emits an event carrying an input range, optionally followed by a payload
+/// byte.
 pub fn event_with_range(input: TokenStream) -> TokenStream {
   let definition = parse_macro_input!(input as EventRequest);
   let callback = &definition.identifier;
@@ -13,7 +14,15 @@ pub fn event_with_range(input: TokenStream) -> TokenStream {
   let bitmask = format_ident!("EVENT_ACTIVE_{}", definition.identifier.to_string().to_uppercase());
   let offset = definition.offset.as_ref().expect("event_with_range requires offset");
   let length = definition.length.as_ref().expect("event_with_range requires length");
-  let needed = quote! { 9usize };
+  let (needed, emit_payload) = match definition.payload.as_ref() {
+    Some(payload) => {
+      (
+        quote! { 10usize },
+        quote! { *self.events.add(event_cursor + 9) = #payload; },
+      )
+    }
+    None => (quote! { 9usize }, quote! {}),
+  };
   let emit = quote! {
     let at = (self.position + #offset) as u32;
     let len = (#length) as u32;
@@ -27,12 +36,13 @@ pub fn event_with_range(input: TokenStream) -> TokenStream {
         self.events.add(event_cursor + 5) as *mut u32,
         len.to_le(),
       );
+      #emit_payload
     }
   };
 
   TokenStream::from(quote! {
     if active_events & #bitmask != 0 {
-      if event_cursor + #needed < EVENTS_BUFFER_SIZE {
+      if event_cursor + #needed < self.events_capacity {
         #emit
         event_cursor += #needed;
       } else {
@@ -64,7 +74,7 @@ pub fn event_with_error(input: TokenStream) -> TokenStream {
 
   TokenStream::from(quote! {
     if active_events & #bitmask != 0 {
-      if event_cursor + #needed < EVENTS_BUFFER_SIZE {
+      if event_cursor + #needed < self.events_capacity {
         #emit
         event_cursor += #needed;
       } else {
@@ -82,7 +92,7 @@ pub fn event_with_metadata(input: TokenStream) -> TokenStream {
   let event_type = quote! { #callback_const + 1 };
   let bitmask = format_ident!("EVENT_ACTIVE_{}", definition.identifier.to_string().to_uppercase());
   let offset = definition.offset.unwrap_or_else(|| syn::parse_quote! { 0 });
-  let needed = quote! { 19usize };
+  let needed = quote! { 20usize };
   let emit = quote! {
     let at = (self.position + #offset) as u32;
     let status_or_method = if self.is_request { self.method as u16 } else { self.status as u16 };
@@ -110,12 +120,66 @@ pub fn event_with_metadata(input: TokenStream) -> TokenStream {
         self.events.add(event_cursor + 11) as *mut u64,
         content_length.to_le(),
       );
+      *self.events.add(event_cursor + 19) = self.version_minor;
+    }
+  };
+
+  TokenStream::from(quote! {
+    if active_events & #bitmask != 0 {
+      if event_cursor + #needed < self.events_capacity {
+        #emit
+        event_cursor += #needed;
+      } else {
+        suspend!();
+      }
+    }
+  })
+}
+
+/// Emits an event carrying a decoded HTTP/2 setting.
+pub fn event_with_http2_setting(input: TokenStream) -> TokenStream {
+  let definition = parse_macro_input!(input as EventRequest);
+  let callback = &definition.identifier;
+  let callback_const = format_ident!("CALLBACK_{}", callback.to_string().to_uppercase());
+  let event_type = quote! { #callback_const + 1 };
+  let bitmask = format_ident!("EVENT_ACTIVE_{}", definition.identifier.to_string().to_uppercase());
+  let offset = definition
+    .offset
+    .as_ref()
+    .expect("event_with_http2_setting requires offset");
+  let identifier = definition
+    .length
+    .as_ref()
+    .expect("event_with_http2_setting requires identifier");
+  let value = definition
+    .payload
+    .as_ref()
+    .expect("event_with_http2_setting requires value");
+  let needed = quote! { 11usize };
+  let emit = quote! {
+    let at = (self.position + #offset) as u32;
+    let identifier = (#identifier) as u16;
+    let value = (#value) as u32;
+    unsafe {
+      *self.events.add(event_cursor) = #event_type;
+      core::ptr::write_unaligned(
+        self.events.add(event_cursor + 1) as *mut u32,
+        at.to_le(),
+      );
+      core::ptr::write_unaligned(
+        self.events.add(event_cursor + 5) as *mut u16,
+        identifier.to_le(),
+      );
+      core::ptr::write_unaligned(
+        self.events.add(event_cursor + 7) as *mut u32,
+        value.to_le(),
+      );
     }
   };
 
   TokenStream::from(quote! {
     if active_events & #bitmask != 0 {
-      if event_cursor + #needed < EVENTS_BUFFER_SIZE {
+      if event_cursor + #needed < self.events_capacity {
         #emit
         event_cursor += #needed;
       } else {
@@ -162,7 +226,7 @@ pub fn fail(input: TokenStream) -> TokenStream {
   let message = definition.message;
 
   TokenStream::from(quote! {
-    self.fail(#error, #message);
+    self.fail_with_events(#error, #message, &mut event_cursor);
     break 'parser;
   })
 }
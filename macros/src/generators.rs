@@ -3,15 +3,27 @@ use quote::{format_ident, quote};
 use regex::{Captures, Regex};
 use syn::{Arm, ItemConst, parse_str};
 
-use crate::{native, parser_fields, wasm};
-
-fn init_constants() -> (Vec<String>, Vec<String>, Vec<String>, Vec<String>) {
+use crate::{handler, matchers, native, parser_fields, wasm};
+
+// Methods, headers, errors, callbacks, states and lenient flags.
+type Constants = (
+  Vec<String>,
+  Vec<String>,
+  Vec<String>,
+  Vec<String>,
+  Vec<String>,
+  Vec<String>,
+);
+
+fn init_constants() -> Constants {
   let methods = serde_yaml::from_str(include_str!("../constants/methods.yml")).unwrap();
+  let headers = serde_yaml::from_str(include_str!("../constants/headers.yml")).unwrap();
   let errors = serde_yaml::from_str(include_str!("../constants/errors.yml")).unwrap();
   let callbacks = serde_yaml::from_str(include_str!("../constants/callbacks.yml")).unwrap();
   let states = serde_yaml::from_str(include_str!("../constants/states.yml")).unwrap();
+  let lenient = serde_yaml::from_str(include_str!("../constants/lenient.yml")).unwrap();
 
-  (methods, errors, callbacks, states)
+  (methods, headers, errors, callbacks, states, lenient)
 }
 
 fn generate_constants_internal(
@@ -64,13 +76,22 @@ where
 }
 
 /// Generates all parser constants.
-fn generate_constants(methods: &[String], errors: &[String], callbacks: &[String], states: &[String]) -> TokenStream {
+fn generate_constants(
+  methods: &[String],
+  headers: &[String],
+  errors: &[String],
+  callbacks: &[String],
+  states: &[String],
+  lenient: &[String],
+) -> TokenStream {
   let methods_consts = generate_constants_internal(methods, "METHOD", 0, None);
+  let headers_consts = generate_constants_internal(headers, "HEADER", 0, None);
   let states_consts = generate_constants_internal(states, "STATE", 0, None);
   let errors_consts = generate_constants_internal(errors, "ERROR", 0, None);
   let callbacks_consts = generate_constants_internal(callbacks, "CALLBACK", 0, None);
   let callbacks_bitmask = generate_bitmask(callbacks, "CALLBACK_ACTIVE");
   let event_bitmask = generate_bitmask(callbacks, "EVENT_ACTIVE");
+  let lenient_bitmask = generate_bitmask(lenient, "LENIENT");
   let event_consts = generate_constants_internal(callbacks, "EVENT", 1, Some("on_"));
   let parser_field_offsets = parser_fields::generate_constants();
   let token_table = generate_table(|byte| {
@@ -130,6 +151,7 @@ fn generate_constants(methods: &[String], errors: &[String], callbacks: &[String
     pub type Callback = fn (&mut Parser, usize, usize);
 
     #(#methods_consts)*
+    #(#headers_consts)*
     #(#errors_consts)*
     #(#callbacks_consts)*
     pub const EVENT_END: u8 = 0;
@@ -137,9 +159,23 @@ fn generate_constants(methods: &[String], errors: &[String], callbacks: &[String
     #(#callbacks_bitmask)*
     #(#event_bitmask)*
     #(#states_consts)*
+    #(#lenient_bitmask)*
     #parser_field_offsets
 
-    const EVENTS_BUFFER_SIZE: usize = 64 * 1024;
+    /// The default capacity of the events buffer.
+    pub const EVENTS_BUFFER_SIZE: usize = 64 * 1024;
+
+    /// The minimum capacity of an events buffer.
+    pub const EVENTS_MIN_BUFFER_SIZE: usize = 2 * EVENTS_MAX_STEP_SIZE;
+
+    // The maximum size of the events emitted by a single iteration of the parser loop.
+    const EVENTS_MAX_STEP_SIZE: usize = 256;
+
+    // The maximum number of protocols accepted in a Upgrade header when the protocol events are active. It bounds the events emitted by a single header.
+    const UPGRADE_MAX_PROTOCOLS: usize = 8;
+
+    // The maximum number of settings accepted in a HTTP2-Settings header when the setting events are active. It bounds the events emitted by a single header.
+    const HTTP2_MAX_SETTINGS: usize = 16;
 
     /// cbindgen:ignore
     static TOKEN_TABLE: [bool; 256] = [#(#token_table),*];
@@ -156,14 +192,24 @@ fn generate_constants(methods: &[String], errors: &[String], callbacks: &[String
 }
 
 /// Generates all parser enums.
-fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], states: &[String]) -> TokenStream {
+fn generate_enums(
+  methods: &[String],
+  headers: &[String],
+  errors: &[String],
+  callbacks: &[String],
+  states: &[String],
+) -> TokenStream {
   let snake_matcher = Regex::new(r"_([a-z])").unwrap();
 
   let methods_ref = methods;
   let errors_ref = errors;
   let callbacks_ref = callbacks;
   let events_ref: Vec<String> = core::iter::once("END".to_string())
-    .chain(callbacks_ref.iter().map(|x| x.strip_prefix("on_").unwrap_or(x).to_string()))
+    .chain(
+      callbacks_ref
+        .iter()
+        .map(|x| x.strip_prefix("on_").unwrap_or(x).to_string()),
+    )
     .collect();
   let states_ref = states;
 
@@ -172,6 +218,12 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
     .map(|x| format_ident!("{}", x.replace('-', "_")))
     .collect();
 
+  let headers_ref = headers;
+  let headers: Vec<_> = headers_ref
+    .iter()
+    .map(|x| format_ident!("{}", x.to_uppercase().replace('-', "_")))
+    .collect();
+
   let errors: Vec<_> = errors_ref.iter().map(|x| format_ident!("{}", x)).collect();
 
   let callbacks: Vec<_> = callbacks_ref
@@ -186,7 +238,10 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
     })
     .collect();
 
-  let events: Vec<_> = events_ref.iter().map(|x| format_ident!("{}", x.to_uppercase())).collect();
+  let events: Vec<_> = events_ref
+    .iter()
+    .map(|x| format_ident!("{}", x.to_uppercase()))
+    .collect();
 
   let states: Vec<_> = states_ref
     .iter()
@@ -199,6 +254,12 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
     .map(|(x, i)| parse_str::<Arm>(&format!("{} => Ok(Methods::{})", x, i.replace('-', "_"))).unwrap())
     .collect();
 
+  let headers_from: Vec<_> = headers
+    .iter()
+    .enumerate()
+    .map(|(x, i)| parse_str::<Arm>(&format!("{} => Ok(Headers::{})", x, i)).unwrap())
+    .collect();
+
   let errors_from: Vec<_> = errors_ref
     .iter()
     .enumerate()
@@ -228,6 +289,12 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
     .map(|x| parse_str::<Arm>(&format!("Methods::{} => \"{}\"", x.replace('-', "_"), x)).unwrap())
     .collect();
 
+  let headers_into: Vec<_> = headers
+    .iter()
+    .zip(headers_ref)
+    .map(|(x, name)| parse_str::<Arm>(&format!("Headers::{} => \"{}\"", x, name)).unwrap())
+    .collect();
+
   let errors_into: Vec<_> = errors_ref
     .iter()
     .map(|x| parse_str::<Arm>(&format!("Errors::{} => \"{}\"", x, x)).unwrap())
@@ -250,31 +317,37 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
 
   TokenStream::from(quote! {
     #[repr(u8)]
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum Methods {
       #(#methods),*
     }
 
     #[repr(u8)]
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum Headers {
+      #(#headers),*
+    }
+
+    #[repr(u8)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum Errors {
       #(#errors),*
     }
 
     #[repr(u8)]
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum Callbacks {
       #(#callbacks),*
     }
 
     #[repr(u8)]
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum Events {
       #(#events),*
     }
 
     #[repr(u8)]
-    #[derive(Copy, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
     pub enum States {
       #(#states),*
     }
@@ -290,6 +363,17 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
       }
     }
 
+    impl TryFrom<u8> for Headers {
+      type Error = ();
+
+      fn try_from(value: u8) -> Result<Self, ()> {
+        match value {
+          #(#headers_from),*,
+          _ => Err(())
+        }
+      }
+    }
+
     impl TryFrom<u8> for Errors {
       type Error = ();
 
@@ -342,6 +426,14 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
       }
     }
 
+    impl From<Headers> for &str {
+      fn from(value: Headers) -> Self {
+        match value {
+          #(#headers_into),*
+        }
+      }
+    }
+
     impl From<Errors> for &str {
       fn from(value: Errors) -> Self {
         match value {
@@ -380,6 +472,12 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
       }
     }
 
+    impl Headers {
+      pub fn as_str(self) -> &'static str {
+        self.into()
+      }
+    }
+
     impl Errors {
       pub fn as_str(self) -> &'static str {
         self.into()
@@ -409,17 +507,44 @@ fn generate_enums(methods: &[String], errors: &[String], callbacks: &[String], s
 fn generate_callbacks(callbacks: &[String]) -> TokenStream {
   let native = native::generate_callbacks(callbacks);
   let wasm = wasm::generate_callbacks(callbacks);
+  let handler = handler::generate_handler(callbacks);
+
+  TokenStream::from_iter([native, wasm, handler])
+}
 
-  TokenStream::from_iter([native, wasm])
+/// Generates the matcher of the known header names.
+fn generate_header_matcher(headers: &[String]) -> TokenStream {
+  let arms: Vec<_> = headers
+    .iter()
+    .skip(1)
+    .map(|x| {
+      let pattern = matchers::case_insensitive_pattern(x);
+      let constant = format_ident!("HEADER_{}", x.to_uppercase().replace('-', "_"));
+
+      quote! { #pattern => #constant }
+    })
+    .collect();
+
+  TokenStream::from(quote! {
+    /// Returns the known header matching a header name, ignoring case.
+    #[inline(always)]
+    pub(crate) fn known_header(name: &[u8]) -> u8 {
+      match name {
+        #(#arms),*,
+        _ => HEADER_OTHER
+      }
+    }
+  })
 }
 
 /// Generates the complete parser.
 pub fn generate() -> TokenStream {
-  let (methods, errors, callbacks, states) = init_constants();
+  let (methods, headers, errors, callbacks, states, lenient) = init_constants();
 
-  let constants_code = generate_constants(&methods, &errors, &callbacks, &states);
-  let enums_code = generate_enums(&methods, &errors, &callbacks, &states);
+  let constants_code = generate_constants(&methods, &headers, &errors, &callbacks, &states, &lenient);
+  let enums_code = generate_enums(&methods, &headers, &errors, &callbacks, &states);
+  let header_matcher_code = generate_header_matcher(&headers);
   let callbacks_code = generate_callbacks(&callbacks);
 
-  TokenStream::from_iter([constants_code, enums_code, callbacks_code])
+  TokenStream::from_iter([constants_code, enums_code, header_matcher_code, callbacks_code])
 }
@@ -0,0 +1,93 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+
+/// Generates the `Handler` trait and its monomorphized dispatcher.
+pub fn generate_handler(callbacks: &[String]) -> TokenStream {
+  let range_callbacks: Vec<_> = callbacks
+    .iter()
+    .filter(|x| {
+      !matches!(
+        x.as_str(),
+        "on_error" | "on_headers" | "on_state_change" | "on_header_name" | "on_http2_setting"
+      )
+    })
+    .map(|x| format_ident!("{}", x))
+    .collect();
+
+  let range_events: Vec<_> = range_callbacks
+    .iter()
+    .map(|callback| {
+      let callback_name = callback.to_string();
+      format_ident!(
+        "{}",
+        callback_name
+          .strip_prefix("on_")
+          .unwrap_or(&callback_name)
+          .to_uppercase()
+      )
+    })
+    .collect();
+
+  TokenStream::from(quote! {
+    /// A set of typed callbacks, one per parser event.
+    ///
+    /// All methods default to a no-op, so implementors only override the events they are interested in.
+    #[allow(unused_variables)]
+    pub trait Handler {
+      fn on_error(&mut self, parser: &mut Parser, code: Errors) {}
+      fn on_headers(&mut self, parser: &mut Parser, metadata: HeadersMetadata) {}
+      fn on_state_change(&mut self, parser: &mut Parser, state: States) {}
+      fn on_header_name(&mut self, parser: &mut Parser, data: &[u8], header: Headers) {}
+      fn on_http2_setting(&mut self, parser: &mut Parser, identifier: u16, value: u32) {}
+      #( fn #range_callbacks(&mut self, parser: &mut Parser, data: &[u8]) {} )*
+    }
+
+    impl Parser {
+      /// Parses a slice of bytes, dispatching the emitted events to a handler.
+      ///
+      /// It returns the number of consumed characters.
+      pub fn parse_with<H: Handler>(&mut self, input: &[u8], handler: &mut H) -> usize {
+        let consumed = self.parse(input.as_ptr(), input.len());
+        self.dispatch_events(input, handler);
+        consumed
+      }
+
+      /// Dispatches the events emitted by the last `parse` call to a handler,
+      /// resolving ranges against the `input` passed to it.
+      pub fn dispatch_events<H: Handler>(&mut self, input: &[u8], handler: &mut H) {
+        if self.active_events | self.active_callbacks == 0 {
+          return;
+        }
+
+        let mut cursor = 0usize;
+
+        loop {
+          // Decode each event before dispatching so that no borrow of the buffer outlives the handler call
+          let events = unsafe { core::slice::from_raw_parts(self.events, self.events_capacity) };
+          let Some((event, size)) = crate::events::decode_event(events, cursor, input) else {
+            break;
+          };
+
+          match event {
+            Event::Error { code, .. } => handler.on_error(self, code),
+            Event::Headers(metadata) => handler.on_headers(self, metadata),
+            Event::StateChange { state, .. } => handler.on_state_change(self, state),
+            Event::HeaderName { data, header, .. } => {
+              self.known_header = header as u8;
+              handler.on_header_name(self, data, header)
+            }
+            Event::Http2Setting { identifier, value, .. } => {
+              self.http2_setting_identifier = identifier;
+              self.http2_setting_value = value;
+              handler.on_http2_setting(self, identifier, value)
+            }
+            #( Event::Range { event: Events::#range_events, data, .. } => handler.#range_callbacks(self, data), )*
+            _ => {}
+          }
+
+          cursor += size;
+        }
+      }
+    }
+  })
+}
@@ -2,6 +2,7 @@ use proc_macro::TokenStream;
 
 mod actions;
 mod generators;
+mod handler;
 mod matchers;
 mod native;
 mod parser_fields;
@@ -23,6 +24,9 @@ pub fn event_with_error(input: TokenStream) -> TokenStream { actions::event_with
 #[proc_macro]
 pub fn event_with_metadata(input: TokenStream) -> TokenStream { actions::event_with_metadata(input) }
 
+#[proc_macro]
+pub fn event_with_http2_setting(input: TokenStream) -> TokenStream { actions::event_with_http2_setting(input) }
+
 #[proc_macro]
 pub fn advance(input: TokenStream) -> TokenStream { actions::advance(input) }
 
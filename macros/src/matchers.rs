@@ -1,4 +1,5 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{Expr, LitStr, parse_macro_input, parse_str};
 
@@ -6,11 +7,15 @@ use syn::{Expr, LitStr, parse_macro_input, parse_str};
 pub fn case_insensitive_string(input: TokenStream) -> TokenStream {
   let definition = parse_macro_input!(input as LitStr);
 
-  let bytes: Vec<_> = definition
-    .value()
+  TokenStream::from(case_insensitive_pattern(&definition.value()))
+}
+
+/// Builds a slice pattern matching a string in case insensitive way.
+pub fn case_insensitive_pattern(value: &str) -> TokenStream2 {
+  let bytes: Vec<_> = value
     .bytes()
     .map(|b| parse_str::<Expr>(&format!("{} | {}", b.to_ascii_uppercase(), b.to_ascii_lowercase())).unwrap())
     .collect();
 
-  TokenStream::from(quote! { [#(#bytes),*] })
+  quote! { [#(#bytes),*] }
 }
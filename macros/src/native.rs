@@ -32,7 +32,34 @@ pub fn generate_callbacks(callbacks: &[String]) -> TokenStream {
           if self.active_callbacks & #active_const != 0 {
             (self.callbacks.#callback)(self, at, 0);
           }
-          cursor += 19usize;
+          cursor += 20usize;
+        }
+      }
+    } else if callback_name == "on_header_name" {
+      quote! {
+        #event_const => {
+          let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
+          let len = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u32) }.to_le() as usize;
+          if self.active_callbacks & #active_const != 0 {
+            self.known_header = unsafe { *self.events.add(cursor + 9) };
+            (self.callbacks.#callback)(self, at, len);
+          }
+          cursor += 10usize;
+        }
+      }
+    } else if callback_name == "on_http2_setting" {
+      quote! {
+        #event_const => {
+          let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
+          if self.active_callbacks & #active_const != 0 {
+            self.http2_setting_identifier =
+              unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u16) }.to_le();
+            self.http2_setting_value =
+              unsafe { core::ptr::read_unaligned(self.events.add(cursor + 7) as *const u32) }.to_le();
+            // The setting is only available via the parser fields, so no size is reported
+            (self.callbacks.#callback)(self, at, 0);
+          }
+          cursor += 11usize;
         }
       }
     } else {
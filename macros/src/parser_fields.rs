@@ -6,6 +6,25 @@ use quote::{format_ident, quote};
 type WasmPointer = u32;
 type WasmUsize = u32;
 
+// Keep this in sync with parser::ParserConfig when compiled with
+// target_family = "wasm".
+#[repr(C)]
+struct ParserConfigStub {
+  max_start_line_length: WasmUsize,
+  max_header_length: WasmUsize,
+  max_body_payload: u64,
+  max_unconsumed_length: WasmUsize,
+  lenient_flags: u64,
+  message_type: u8,
+  protocol: u8,
+  suspend_after_headers: bool,
+  manage_unconsumed: bool,
+  skip_body: bool,
+  debug: bool,
+  allow_http10: bool,
+  emit_tunnel_data: bool,
+}
+
 // Keep this in sync with parser::Parser when compiled with target_family =
 // "wasm".
 #[repr(C)]
@@ -13,6 +32,9 @@ struct ParserStub {
   max_start_line_length: WasmUsize,
   max_header_length: WasmUsize,
   max_body_payload: u64,
+  max_unconsumed_length: WasmUsize,
+  lenient_flags: u64,
+  protocol: u8,
   autodetect: bool,
   is_request: bool,
   suspend_after_headers: bool,
@@ -20,24 +42,40 @@ struct ParserStub {
   continue_without_data: bool,
   is_connect: bool,
   skip_body: bool,
+  expect_connect_response: bool,
   debug: bool,
+  allow_http10: bool,
+  emit_tunnel_data: bool,
+  config: ParserConfigStub,
+  has_config: bool,
   parsed: u64,
   position: WasmUsize,
+  error_offset: u64,
   state: u8,
   paused: bool,
   error_code: u8,
+  error_state: u8,
   content_length: u64,
   chunk_size: u64,
   remaining_content_length: u64,
   remaining_chunk_size: u64,
+  tunnel_bytes: u64,
   status: u32,
+  http2_setting_value: u32,
+  http2_setting_identifier: u16,
   method: u8,
+  known_header: u8,
+  version_minor: u8,
   has_content_length: bool,
   has_transfer_encoding: bool,
   has_chunked_transfer_encoding: bool,
   has_connection_close: bool,
+  has_connection_keep_alive: bool,
   has_connection_upgrade: bool,
   has_upgrade: bool,
+  has_upgrade_h2c: bool,
+  has_http2_settings: bool,
+  has_connection_http2_settings: bool,
   has_trailers: bool,
   active_callbacks: u64,
   active_events: u64,
@@ -45,14 +83,23 @@ struct ParserStub {
   error_description: [u8; 255],
   unconsumed: WasmPointer,
   unconsumed_len: WasmUsize,
+  unconsumed_buffer: WasmPointer,
+  unconsumed_capacity: WasmUsize,
+  unconsumed_allocations: u64,
   error_description_len: u8,
+  owns_events: bool,
+  events_capacity: WasmUsize,
   events: WasmPointer,
+  owned_context: WasmPointer,
 }
 
 const FIELDS: &[(&str, usize)] = &[
   ("MAX_START_LINE_LENGTH", offset_of!(ParserStub, max_start_line_length)),
   ("MAX_HEADER_LENGTH", offset_of!(ParserStub, max_header_length)),
   ("MAX_BODY_PAYLOAD", offset_of!(ParserStub, max_body_payload)),
+  ("MAX_UNCONSUMED_LENGTH", offset_of!(ParserStub, max_unconsumed_length)),
+  ("LENIENT_FLAGS", offset_of!(ParserStub, lenient_flags)),
+  ("PROTOCOL", offset_of!(ParserStub, protocol)),
   ("AUTODETECT", offset_of!(ParserStub, autodetect)),
   ("IS_REQUEST", offset_of!(ParserStub, is_request)),
   ("SUSPEND_AFTER_HEADERS", offset_of!(ParserStub, suspend_after_headers)),
@@ -60,12 +107,22 @@ const FIELDS: &[(&str, usize)] = &[
   ("CONTINUE_WITHOUT_DATA", offset_of!(ParserStub, continue_without_data)),
   ("IS_CONNECT", offset_of!(ParserStub, is_connect)),
   ("SKIP_BODY", offset_of!(ParserStub, skip_body)),
+  (
+    "EXPECT_CONNECT_RESPONSE",
+    offset_of!(ParserStub, expect_connect_response),
+  ),
   ("DEBUG", offset_of!(ParserStub, debug)),
+  ("ALLOW_HTTP10", offset_of!(ParserStub, allow_http10)),
+  ("EMIT_TUNNEL_DATA", offset_of!(ParserStub, emit_tunnel_data)),
+  ("CONFIG", offset_of!(ParserStub, config)),
+  ("HAS_CONFIG", offset_of!(ParserStub, has_config)),
   ("PARSED", offset_of!(ParserStub, parsed)),
   ("POSITION", offset_of!(ParserStub, position)),
+  ("ERROR_OFFSET", offset_of!(ParserStub, error_offset)),
   ("STATE", offset_of!(ParserStub, state)),
   ("PAUSED", offset_of!(ParserStub, paused)),
   ("ERROR_CODE", offset_of!(ParserStub, error_code)),
+  ("ERROR_STATE", offset_of!(ParserStub, error_state)),
   ("CONTENT_LENGTH", offset_of!(ParserStub, content_length)),
   ("CHUNK_SIZE", offset_of!(ParserStub, chunk_size)),
   (
@@ -73,8 +130,16 @@ const FIELDS: &[(&str, usize)] = &[
     offset_of!(ParserStub, remaining_content_length),
   ),
   ("REMAINING_CHUNK_SIZE", offset_of!(ParserStub, remaining_chunk_size)),
+  ("TUNNEL_BYTES", offset_of!(ParserStub, tunnel_bytes)),
   ("STATUS", offset_of!(ParserStub, status)),
+  ("HTTP2_SETTING_VALUE", offset_of!(ParserStub, http2_setting_value)),
+  (
+    "HTTP2_SETTING_IDENTIFIER",
+    offset_of!(ParserStub, http2_setting_identifier),
+  ),
   ("METHOD", offset_of!(ParserStub, method)),
+  ("KNOWN_HEADER", offset_of!(ParserStub, known_header)),
+  ("VERSION_MINOR", offset_of!(ParserStub, version_minor)),
   ("HAS_CONTENT_LENGTH", offset_of!(ParserStub, has_content_length)),
   ("HAS_TRANSFER_ENCODING", offset_of!(ParserStub, has_transfer_encoding)),
   (
@@ -82,8 +147,18 @@ const FIELDS: &[(&str, usize)] = &[
     offset_of!(ParserStub, has_chunked_transfer_encoding),
   ),
   ("HAS_CONNECTION_CLOSE", offset_of!(ParserStub, has_connection_close)),
+  (
+    "HAS_CONNECTION_KEEP_ALIVE",
+    offset_of!(ParserStub, has_connection_keep_alive),
+  ),
   ("HAS_CONNECTION_UPGRADE", offset_of!(ParserStub, has_connection_upgrade)),
   ("HAS_UPGRADE", offset_of!(ParserStub, has_upgrade)),
+  ("HAS_UPGRADE_H2C", offset_of!(ParserStub, has_upgrade_h2c)),
+  ("HAS_HTTP2_SETTINGS", offset_of!(ParserStub, has_http2_settings)),
+  (
+    "HAS_CONNECTION_HTTP2_SETTINGS",
+    offset_of!(ParserStub, has_connection_http2_settings),
+  ),
   ("HAS_TRAILERS", offset_of!(ParserStub, has_trailers)),
   ("ACTIVE_CALLBACKS", offset_of!(ParserStub, active_callbacks)),
   ("ACTIVE_EVENTS", offset_of!(ParserStub, active_events)),
@@ -91,7 +166,12 @@ const FIELDS: &[(&str, usize)] = &[
   ("ERROR_DESCRIPTION", offset_of!(ParserStub, error_description)),
   ("UNCONSUMED", offset_of!(ParserStub, unconsumed)),
   ("UNCONSUMED_LEN", offset_of!(ParserStub, unconsumed_len)),
+  ("UNCONSUMED_BUFFER", offset_of!(ParserStub, unconsumed_buffer)),
+  ("UNCONSUMED_CAPACITY", offset_of!(ParserStub, unconsumed_capacity)),
+  ("UNCONSUMED_ALLOCATIONS", offset_of!(ParserStub, unconsumed_allocations)),
   ("ERROR_DESCRIPTION_LEN", offset_of!(ParserStub, error_description_len)),
+  ("OWNS_EVENTS", offset_of!(ParserStub, owns_events)),
+  ("EVENTS_CAPACITY", offset_of!(ParserStub, events_capacity)),
   ("EVENTS", offset_of!(ParserStub, events)),
 ];
 
@@ -14,6 +14,7 @@ pub struct EventRequest {
   pub identifier: Ident,
   pub offset: Option<Expr>,
   pub length: Option<Expr>,
+  pub payload: Option<Expr>,
 }
 
 impl Parse for FailureRequest {
@@ -38,6 +39,7 @@ impl Parse for EventRequest {
     let identifier = input.parse()?;
     let mut offset = None;
     let mut length = None;
+    let mut payload = None;
 
     // If there is more input
     if !input.is_empty() {
@@ -53,6 +55,14 @@ impl Parse for EventRequest {
 
         // Parse the expression
         length = Some(input.parse::<Expr>()?);
+
+        if !input.is_empty() {
+          // Discard the comma
+          input.parse::<Token![,]>()?;
+
+          // Parse the expression
+          payload = Some(input.parse::<Expr>()?);
+        }
       }
     }
 
@@ -60,6 +70,7 @@ impl Parse for EventRequest {
       identifier,
       offset,
       length,
+      payload,
     })
   }
 }
@@ -10,7 +10,7 @@ pub fn generate_callbacks(callbacks: &[String]) -> TokenStream {
     .collect();
   let replay_arms = callbacks
     .iter()
-    .filter(|callback| callback.to_string() != "on_error")
+    .filter(|callback| *callback != "on_error")
     .map(|callback| {
       let callback_name = callback.to_string();
       let event_const = format_ident!(
@@ -22,14 +22,43 @@ pub fn generate_callbacks(callbacks: &[String]) -> TokenStream {
       );
       let active_const = format_ident!("CALLBACK_ACTIVE_{}", callback_name.to_uppercase());
 
-      quote! {
-        #event_const => {
-          let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
-          let len = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u32) }.to_le() as usize;
-          if self.active_callbacks & #active_const != 0 {
-            unsafe { #callback(self.ptr, at, len); }
+      if callback_name == "on_header_name" {
+        quote! {
+          #event_const => {
+            let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
+            let len = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u32) }.to_le() as usize;
+            if self.active_callbacks & #active_const != 0 {
+              self.known_header = unsafe { *self.events.add(cursor + 9) };
+              unsafe { #callback(self.ptr, at, len); }
+            }
+            cursor += 10usize;
+          }
+        }
+      } else if callback_name == "on_http2_setting" {
+        quote! {
+          #event_const => {
+            let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
+            if self.active_callbacks & #active_const != 0 {
+              self.http2_setting_identifier =
+                unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u16) }.to_le();
+              self.http2_setting_value =
+                unsafe { core::ptr::read_unaligned(self.events.add(cursor + 7) as *const u32) }.to_le();
+              // The setting is only available via the parser fields, so no size is reported
+              unsafe { #callback(self.ptr, at, 0); }
+            }
+            cursor += 11usize;
+          }
+        }
+      } else {
+        quote! {
+          #event_const => {
+            let at = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 1) as *const u32) }.to_le() as usize;
+            let len = unsafe { core::ptr::read_unaligned(self.events.add(cursor + 5) as *const u32) }.to_le() as usize;
+            if self.active_callbacks & #active_const != 0 {
+              unsafe { #callback(self.ptr, at, len); }
+            }
+            cursor += 9usize;
           }
-          cursor += 9usize;
         }
       }
     });
@@ -103,7 +132,7 @@ pub fn generate_callbacks(callbacks: &[String]) -> TokenStream {
                   );
                 }
               }
-              cursor += 19usize;
+              cursor += 20usize;
             }
             #(#replay_arms)*
             _ => break,
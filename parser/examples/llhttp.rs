@@ -210,7 +210,7 @@ fn show_test(case: TestCase, section: &str) {
     .output
     .unwrap()
     .iter()
-    .map(|event| {
+    .filter_map(|event| {
       let offset = event.offset;
 
       if event.kind == "request" || event.kind == "response" {
@@ -264,13 +264,9 @@ fn show_test(case: TestCase, section: &str) {
             }
           }
         }
-        _ => {
-          return Some(format!("off={} {}", event.offset, event.kind));
-        }
+        _ => Some(format!("off={} {}", event.offset, event.kind)),
       }
     })
-    .filter(Option::is_some)
-    .map(Option::unwrap)
     .collect::<Vec<String>>();
 
   let lines = llhttp.len().max(milo.len());
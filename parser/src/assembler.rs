@@ -0,0 +1,368 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::*;
+
+const ASSEMBLER_EVENTS: u64 = EVENT_ACTIVE_ON_REQUEST
+  | EVENT_ACTIVE_ON_RESPONSE
+  | EVENT_ACTIVE_ON_METHOD
+  | EVENT_ACTIVE_ON_URL
+  | EVENT_ACTIVE_ON_VERSION
+  | EVENT_ACTIVE_ON_REASON
+  | EVENT_ACTIVE_ON_HEADER_NAME
+  | EVENT_ACTIVE_ON_HEADER_VALUE
+  | EVENT_ACTIVE_ON_HEADERS
+  | EVENT_ACTIVE_ON_CONNECT
+  | EVENT_ACTIVE_ON_UPGRADE
+  | EVENT_ACTIVE_ON_DATA
+  | EVENT_ACTIVE_ON_TRAILER_NAME
+  | EVENT_ACTIVE_ON_TRAILER_VALUE
+  | EVENT_ACTIVE_ON_MESSAGE_COMPLETE
+  | EVENT_ACTIVE_ON_ERROR;
+
+/// A header or trailer field.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+  pub name: String,
+  pub value: Vec<u8>,
+}
+
+/// An assembled HTTP request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+  pub method: Methods,
+  /// The method as received, which is the only way to know it when `method`
+  /// is `Methods::OTHER`.
+  pub method_name: String,
+  pub url: String,
+  pub version: String,
+  pub headers: Vec<Header>,
+  pub body: Vec<u8>,
+  pub trailers: Vec<Header>,
+  pub keep_alive: bool,
+}
+
+/// An assembled HTTP response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Response {
+  pub status: u32,
+  pub reason: String,
+  pub version: String,
+  pub headers: Vec<Header>,
+  pub body: Vec<u8>,
+  pub trailers: Vec<Header>,
+  pub keep_alive: bool,
+}
+
+/// An assembled HTTP message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+  Request(Request),
+  Response(Response),
+}
+
+/// An item produced by a `MessageAssembler`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Assembled {
+  /// A complete message, including its body.
+  Message(Message),
+  /// The head of a message whose body is streamed. The body is always empty.
+  Head(Message),
+  /// A chunk of a streamed body.
+  Body(Vec<u8>),
+  /// The end of a message whose body is streamed.
+  End { trailers: Vec<Header> },
+}
+
+impl Message {
+  fn headers_mut(&mut self) -> &mut Vec<Header> {
+    match self {
+      Message::Request(request) => &mut request.headers,
+      Message::Response(response) => &mut response.headers,
+    }
+  }
+
+  fn body_mut(&mut self) -> &mut Vec<u8> {
+    match self {
+      Message::Request(request) => &mut request.body,
+      Message::Response(response) => &mut response.body,
+    }
+  }
+
+  pub(crate) fn trailers_mut(&mut self) -> &mut Vec<Header> {
+    match self {
+      Message::Request(request) => &mut request.trailers,
+      Message::Response(response) => &mut response.trailers,
+    }
+  }
+}
+
+/// Assembles owned messages from the event stream of a parser.
+///
+/// Bodies are buffered in the assembled message, unless the parser has a
+/// `max_body_payload` set. In that case each message is produced as a
+/// `Head`, followed by `Body` chunks of at most `max_body_payload` bytes and
+/// by an `End`.
+///
+/// A message entering tunnel mode (a `CONNECT` request or a `101` response)
+/// is produced once its head is complete. The data following it is retained
+/// by the parser as unconsumed data.
+#[derive(Debug)]
+pub struct MessageAssembler {
+  parser: Parser,
+  pending: Option<Message>,
+  output: VecDeque<Assembled>,
+}
+
+impl Default for MessageAssembler {
+  fn default() -> Self { Self::new() }
+}
+
+impl MessageAssembler {
+  /// Creates a new assembler with a default parser.
+  pub fn new() -> MessageAssembler { Self::with_parser(Parser::new()) }
+
+  /// Creates a new assembler using an existing parser.
+  ///
+  /// The events needed by the assembler and `manage_unconsumed` are enabled
+  /// on the parser, while `emit_tunnel_data` is disabled so that the tunneled
+  /// data is retained as unconsumed data.
+  pub fn with_parser(mut parser: Parser) -> MessageAssembler {
+    parser.active_events |= ASSEMBLER_EVENTS;
+    parser.manage_unconsumed = true;
+    parser.emit_tunnel_data = false;
+
+    MessageAssembler {
+      parser,
+      pending: None,
+      output: VecDeque::new(),
+    }
+  }
+
+  /// Returns the underlying parser.
+  pub fn parser(&self) -> &Parser { &self.parser }
+
+  /// Returns the underlying parser, for instance to set `skip_body`.
+  pub fn parser_mut(&mut self) -> &mut Parser { &mut self.parser }
+
+  /// Consumes the assembler, returning the underlying parser.
+  pub fn into_parser(self) -> Parser { self.parser }
+
+  /// Feeds data to the parser. Assembled items are then available via
+  /// `next_item`.
+  ///
+  /// Data which cannot be parsed yet is retained by the parser and prepended
+  /// to the next invocation.
+  pub fn feed(&mut self, data: &[u8]) -> Result<(), ParseError> {
+    let mut input = data;
+
+    loop {
+      let consumed = self.parse_step(input);
+
+      if let Some(error) = self.parser.error() {
+        return Err(error);
+      }
+
+      input = &[];
+
+      let can_continue = self.parser.unconsumed_len > 0 || self.parser.continue_without_data;
+
+      if self.parser.paused || consumed == 0 || !can_continue {
+        return Ok(());
+      }
+    }
+  }
+
+  /// Signals the end of the data, completing messages delimited by the end
+  /// of the connection.
+  pub fn finish(&mut self) -> Result<(), ParseError> {
+    self.parser.finish();
+
+    if let Some(error) = self.parser.error() {
+      return Err(error);
+    }
+
+    let streaming = self.parser.max_body_payload > 0;
+    let mut failure = None;
+
+    for event in self.parser.iter_events(&[]) {
+      if let Err(description) = Self::process(&mut self.pending, &mut self.output, streaming, event) {
+        failure = Some(description);
+        break;
+      }
+    }
+
+    if let Some(description) = failure {
+      self.pending = None;
+      self.parser.fail(ERROR_UNEXPECTED_DATA, description);
+      return Err(self.parser.error().unwrap());
+    }
+
+    Ok(())
+  }
+
+  /// Returns the next assembled item, if any.
+  pub fn next_item(&mut self) -> Option<Assembled> { self.output.pop_front() }
+
+  fn parse_step(&mut self, input: &[u8]) -> usize {
+    let streaming = self.parser.max_body_payload > 0;
+    let mut events = self.parser.parse_slice(input);
+    let mut failure = None;
+
+    for event in events.by_ref() {
+      if let Err(description) = Self::process(&mut self.pending, &mut self.output, streaming, event) {
+        failure = Some(description);
+        break;
+      }
+    }
+
+    let consumed = events.consumed();
+
+    if let Some(description) = failure {
+      self.pending = None;
+      self.parser.fail(ERROR_UNEXPECTED_DATA, description);
+    }
+
+    consumed
+  }
+
+  fn process(
+    pending: &mut Option<Message>,
+    output: &mut VecDeque<Assembled>,
+    streaming: bool,
+    event: Event<'_>,
+  ) -> Result<(), &'static str> {
+    if build_head(pending, &event)? {
+      if streaming
+        && matches!(event, Event::Headers(_))
+        && let Some(message) = pending
+      {
+        output.push_back(Assembled::Head(message.clone()));
+      }
+
+      return Ok(());
+    }
+
+    if let Event::Range { event, data, .. } = event {
+      match event {
+        Events::TRAILER_NAME => {
+          if let Some(message) = pending {
+            message.trailers_mut().push(Header {
+              name: String::from_utf8_lossy(data).into_owned(),
+              value: Vec::new(),
+            });
+          }
+        }
+        Events::TRAILER_VALUE => {
+          if let Some(field) = pending.as_mut().and_then(|message| message.trailers_mut().last_mut()) {
+            field.value.extend_from_slice(data);
+          }
+        }
+        Events::DATA => {
+          if streaming {
+            output.push_back(Assembled::Body(data.to_vec()));
+          } else if let Some(message) = pending {
+            message.body_mut().extend_from_slice(data);
+          }
+        }
+        // CONNECT requests and upgrades enter tunnel mode without completing the message
+        Events::MESSAGE_COMPLETE | Events::CONNECT | Events::UPGRADE => {
+          if let Some(mut message) = pending.take() {
+            if streaming {
+              output.push_back(Assembled::End {
+                trailers: core::mem::take(message.trailers_mut()),
+              });
+            } else {
+              output.push_back(Assembled::Message(message));
+            }
+          }
+        }
+        _ => (),
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Applies an event contributing to the head of a message, returning `false`
+/// for any other event.
+///
+/// It fails with the error description if the event cannot be applied.
+pub(crate) fn build_head(pending: &mut Option<Message>, event: &Event<'_>) -> Result<bool, &'static str> {
+  match *event {
+    Event::Range { event, data, .. } => {
+      match event {
+        Events::REQUEST => {
+          *pending = Some(Message::Request(Request {
+            method: Methods::OTHER,
+            method_name: String::new(),
+            url: String::new(),
+            version: String::new(),
+            headers: Vec::new(),
+            body: Vec::new(),
+            trailers: Vec::new(),
+            keep_alive: false,
+          }))
+        }
+        Events::RESPONSE => *pending = Some(Message::Response(Response::default())),
+        Events::METHOD => {
+          if let Some(Message::Request(request)) = pending {
+            request.method_name.push_str(&String::from_utf8_lossy(data));
+          }
+        }
+        Events::URL => {
+          if let Some(Message::Request(request)) = pending {
+            request.url.push_str(&String::from_utf8_lossy(data));
+          }
+        }
+        Events::VERSION => {
+          match pending {
+            Some(Message::Request(request)) => request.version.push_str(&String::from_utf8_lossy(data)),
+            Some(Message::Response(response)) => response.version.push_str(&String::from_utf8_lossy(data)),
+            None => (),
+          }
+        }
+        Events::REASON => {
+          if let Some(Message::Response(response)) = pending {
+            response.reason.push_str(&String::from_utf8_lossy(data));
+          }
+        }
+        Events::HEADER_VALUE => {
+          if let Some(field) = pending.as_mut().and_then(|message| message.headers_mut().last_mut()) {
+            field.value.extend_from_slice(data);
+          }
+        }
+        _ => return Ok(false),
+      }
+    }
+    Event::HeaderName { data, .. } => {
+      if let Some(message) = pending {
+        message.headers_mut().push(Header {
+          name: String::from_utf8_lossy(data).into_owned(),
+          value: Vec::new(),
+        });
+      }
+    }
+    Event::Headers(metadata) => {
+      match pending {
+        Some(Message::Request(request)) => {
+          request.method = u8::try_from(metadata.status_or_method)
+            .ok()
+            .and_then(|method| Methods::try_from(method).ok())
+            .ok_or("Invalid method")?;
+          request.keep_alive = metadata.should_keep_alive;
+        }
+        Some(Message::Response(response)) => {
+          response.status = metadata.status_or_method as u32;
+          response.keep_alive = metadata.should_keep_alive;
+        }
+        None => (),
+      }
+    }
+    Event::Http2Setting { .. } | Event::StateChange { .. } | Event::Error { .. } => return Ok(false),
+  }
+
+  Ok(true)
+}
@@ -0,0 +1,372 @@
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use std::io;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::assembler::build_head;
+use crate::matchers::validate_token;
+use crate::*;
+
+const CODEC_EVENTS: u64 = EVENT_ACTIVE_ON_REQUEST
+  | EVENT_ACTIVE_ON_RESPONSE
+  | EVENT_ACTIVE_ON_METHOD
+  | EVENT_ACTIVE_ON_URL
+  | EVENT_ACTIVE_ON_VERSION
+  | EVENT_ACTIVE_ON_REASON
+  | EVENT_ACTIVE_ON_HEADER_NAME
+  | EVENT_ACTIVE_ON_HEADER_VALUE
+  | EVENT_ACTIVE_ON_HEADERS
+  | EVENT_ACTIVE_ON_CONNECT
+  | EVENT_ACTIVE_ON_UPGRADE
+  | EVENT_ACTIVE_ON_DATA
+  | EVENT_ACTIVE_ON_TRAILER_NAME
+  | EVENT_ACTIVE_ON_TRAILER_VALUE
+  | EVENT_ACTIVE_ON_MESSAGE_COMPLETE
+  | EVENT_ACTIVE_ON_ERROR;
+
+/// A frame decoded by `HttpCodec`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Frame {
+  /// The head of a message. Its body and trailers are always empty.
+  Head(Message),
+  /// A chunk of the body of the current message.
+  BodyChunk(Bytes),
+  /// The trailers of the current message, emitted only when not empty.
+  Trailers(Vec<Header>),
+  /// The end of the current message.
+  End,
+  /// Raw data received after the connection entered tunnel mode.
+  Tunnel(Bytes),
+}
+
+/// The error returned by `HttpCodec`.
+#[derive(Debug)]
+pub enum CodecError {
+  /// The underlying I/O failed.
+  Io(io::Error),
+  /// The parser failed.
+  Parse(ParseError),
+  /// The method of an encoded request is not known.
+  InvalidMethod(Methods),
+  /// The name of an encoded header is not a valid token.
+  InvalidHeaderName(String),
+  /// The URL of an encoded request is empty or contains spaces or control
+  /// characters.
+  InvalidUrl(String),
+  /// The version of an encoded message is not in the `DIGIT "." DIGIT` form.
+  InvalidVersion(String),
+  /// The status of an encoded response is not a three digits number.
+  InvalidStatus(u32),
+  /// A field of an encoded message, such as the reason or a header value,
+  /// contains a CR, LF or NUL character.
+  InvalidField(&'static str),
+}
+
+impl fmt::Display for CodecError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      CodecError::Io(error) => write!(f, "I/O error: {error}"),
+      CodecError::Parse(error) => write!(f, "Parse error: {error}"),
+      CodecError::InvalidMethod(method) => write!(f, "Cannot encode method {}", method.as_str()),
+      CodecError::InvalidHeaderName(name) => write!(f, "Cannot encode header name {name:?}"),
+      CodecError::InvalidUrl(url) => write!(f, "Cannot encode URL {url:?}"),
+      CodecError::InvalidVersion(version) => write!(f, "Cannot encode version {version:?}"),
+      CodecError::InvalidStatus(status) => write!(f, "Cannot encode status {status}"),
+      CodecError::InvalidField(field) => write!(f, "Cannot encode {field} containing CR, LF or NUL"),
+    }
+  }
+}
+
+impl core::error::Error for CodecError {}
+
+impl From<io::Error> for CodecError {
+  fn from(error: io::Error) -> Self { CodecError::Io(error) }
+}
+
+impl From<ParseError> for CodecError {
+  fn from(error: ParseError) -> Self { CodecError::Parse(error) }
+}
+
+/// A `tokio_util` codec decoding HTTP messages into frames and encoding
+/// message heads.
+///
+/// Each message is decoded as a `Head` frame followed by any number of
+/// `BodyChunk` frames, an optional `Trailers` frame and an `End` frame. Once
+/// the parser enters tunnel mode (after a `CONNECT` request or an upgrade)
+/// the remaining data is returned as is via `Tunnel` frames.
+///
+/// Data which cannot be parsed yet is left in the read buffer, so
+/// `manage_unconsumed` is disabled on the parser.
+#[derive(Debug)]
+pub struct HttpCodec {
+  parser: Parser,
+  pending: Option<Message>,
+  frames: VecDeque<Frame>,
+  tunnel: bool,
+  finished: bool,
+}
+
+impl Default for HttpCodec {
+  fn default() -> Self { Self::new() }
+}
+
+impl HttpCodec {
+  /// Creates a new codec with a default parser.
+  pub fn new() -> HttpCodec { Self::with_parser(Parser::new()) }
+
+  /// Creates a new codec using an existing parser.
+  ///
+  /// The events needed by the codec are enabled, while `manage_unconsumed`
+  /// and `emit_tunnel_data` are disabled on the parser, as the tunneled data
+  /// is returned via `Tunnel` frames.
+  pub fn with_parser(mut parser: Parser) -> HttpCodec {
+    parser.active_events |= CODEC_EVENTS;
+    parser.manage_unconsumed = false;
+    parser.emit_tunnel_data = false;
+
+    HttpCodec {
+      parser,
+      pending: None,
+      frames: VecDeque::new(),
+      tunnel: false,
+      finished: false,
+    }
+  }
+
+  /// Returns the underlying parser.
+  pub fn parser(&self) -> &Parser { &self.parser }
+
+  /// Returns the underlying parser, for instance to set `skip_body`.
+  pub fn parser_mut(&mut self) -> &mut Parser { &mut self.parser }
+
+  /// Returns `true` if the codec is passing data through as `Tunnel` frames.
+  pub fn is_tunnel(&self) -> bool { self.tunnel }
+
+  fn process(&mut self, data: &Bytes) {
+    let HttpCodec {
+      parser,
+      pending,
+      frames,
+      ..
+    } = self;
+
+    let mut failure = None;
+
+    for event in parser.iter_events(data) {
+      match build_head(pending, &event) {
+        Ok(true) => {
+          if let (Event::Headers(_), Some(message)) = (&event, &pending) {
+            frames.push_back(Frame::Head(message.clone()));
+          }
+
+          continue;
+        }
+        Ok(false) => (),
+        Err(description) => {
+          failure = Some(description);
+          break;
+        }
+      }
+
+      let Event::Range { event, at, data: span } = event else {
+        continue;
+      };
+
+      match event {
+        Events::DATA => frames.push_back(Frame::BodyChunk(data.slice(at..at + span.len()))),
+        Events::TRAILER_NAME => {
+          if let Some(message) = pending {
+            message.trailers_mut().push(Header {
+              name: String::from_utf8_lossy(span).into_owned(),
+              value: Vec::new(),
+            });
+          }
+        }
+        Events::TRAILER_VALUE => {
+          if let Some(field) = pending.as_mut().and_then(|message| message.trailers_mut().last_mut()) {
+            field.value.extend_from_slice(span);
+          }
+        }
+        // CONNECT requests and upgrades enter tunnel mode without completing the message
+        Events::MESSAGE_COMPLETE | Events::CONNECT | Events::UPGRADE => {
+          if let Some(mut message) = pending.take() {
+            let trailers = core::mem::take(message.trailers_mut());
+
+            if !trailers.is_empty() {
+              frames.push_back(Frame::Trailers(trailers));
+            }
+
+            frames.push_back(Frame::End);
+          }
+        }
+        _ => (),
+      }
+    }
+
+    if let Some(description) = failure {
+      *pending = None;
+      parser.fail(ERROR_UNEXPECTED_DATA, description);
+    }
+  }
+}
+
+impl Decoder for HttpCodec {
+  type Item = Frame;
+  type Error = CodecError;
+
+  fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+    loop {
+      if let Some(frame) = self.frames.pop_front() {
+        return Ok(Some(frame));
+      }
+
+      if self.tunnel {
+        return Ok((!src.is_empty()).then(|| Frame::Tunnel(src.split().freeze())));
+      }
+
+      let consumed = self.parser.parse(src.as_ptr(), src.len());
+      let data = src.split_to(consumed).freeze();
+      self.process(&data);
+
+      if let Some(error) = self.parser.error() {
+        return Err(error.into());
+      }
+
+      // The tunnel state consumes nothing, so the tunneled data is still in the
+      // buffer
+      if self.parser.state == STATE_TUNNEL {
+        self.tunnel = true;
+      } else if consumed == 0 && !self.parser.continue_without_data && self.frames.is_empty() {
+        return Ok(None);
+      }
+    }
+  }
+
+  fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+    if let Some(frame) = self.decode(src)? {
+      return Ok(Some(frame));
+    }
+
+    if self.tunnel || self.finished {
+      return Ok(None);
+    }
+
+    self.finished = true;
+
+    if src.is_empty() {
+      self.parser.finish();
+      self.process(&Bytes::new());
+    } else {
+      self.parser.fail(ERROR_UNEXPECTED_EOF, "Unexpected end of data");
+    }
+
+    if let Some(error) = self.parser.error() {
+      return Err(error.into());
+    }
+
+    Ok(self.frames.pop_front())
+  }
+}
+
+impl Encoder<Message> for HttpCodec {
+  type Error = CodecError;
+
+  /// Encodes the head of a message. The body, if any, must be written
+  /// separately, framed according to the headers.
+  ///
+  /// The message is validated before anything is written, so that a field
+  /// cannot inject additional lines in the head.
+  fn encode(&mut self, message: Message, dst: &mut BytesMut) -> Result<(), CodecError> {
+    let headers = match &message {
+      Message::Request(request) => &request.headers,
+      Message::Response(response) => &response.headers,
+    };
+
+    for header in headers {
+      if !validate_token(header.name.as_bytes(), 0, header.name.len()) {
+        return Err(CodecError::InvalidHeaderName(header.name.clone()));
+      }
+
+      validate_field(&header.value, "header value")?;
+    }
+
+    match &message {
+      Message::Request(request) => {
+        // Unknown methods can only be encoded using the received name
+        let method = if request.method == Methods::OTHER {
+          request.method_name.as_str()
+        } else {
+          request.method.as_str()
+        };
+
+        if !validate_token(method.as_bytes(), 0, method.len()) {
+          return Err(CodecError::InvalidMethod(request.method));
+        }
+
+        // RFC 9112 section 3.2 - The request target cannot contain whitespace
+        if request.url.is_empty() || request.url.bytes().any(|byte| byte <= b' ' || byte == 0x7f) {
+          return Err(CodecError::InvalidUrl(request.url.clone()));
+        }
+
+        validate_version(&request.version)?;
+
+        dst.put_slice(method.as_bytes());
+        dst.put_u8(b' ');
+        dst.put_slice(request.url.as_bytes());
+        dst.put_slice(b" HTTP/");
+        dst.put_slice(version_or_default(&request.version));
+      }
+      Message::Response(response) => {
+        validate_version(&response.version)?;
+
+        // RFC 9110 section 15 - The status is a three digits number
+        if !(100..=999).contains(&response.status) {
+          return Err(CodecError::InvalidStatus(response.status));
+        }
+
+        validate_field(response.reason.as_bytes(), "reason")?;
+
+        dst.put_slice(b"HTTP/");
+        dst.put_slice(version_or_default(&response.version));
+        dst.put_slice(format!(" {:03} ", response.status).as_bytes());
+        dst.put_slice(response.reason.as_bytes());
+      }
+    }
+
+    dst.put_slice(b"\r\n");
+
+    for header in headers {
+      dst.put_slice(header.name.as_bytes());
+      dst.put_slice(b": ");
+      dst.put_slice(&header.value);
+      dst.put_slice(b"\r\n");
+    }
+
+    dst.put_slice(b"\r\n");
+    Ok(())
+  }
+}
+
+fn validate_field(value: &[u8], field: &'static str) -> Result<(), CodecError> {
+  if value.iter().any(|byte| matches!(byte, b'\r' | b'\n' | 0)) {
+    return Err(CodecError::InvalidField(field));
+  }
+
+  Ok(())
+}
+
+// RFC 9112 section 2.3 - An empty version is encoded as 1.1
+fn validate_version(version: &str) -> Result<(), CodecError> {
+  match version.as_bytes() {
+    [] => Ok(()),
+    [major, b'.', minor] if major.is_ascii_digit() && minor.is_ascii_digit() => Ok(()),
+    _ => Err(CodecError::InvalidVersion(version.into())),
+  }
+}
+
+fn version_or_default(version: &str) -> &[u8] { if version.is_empty() { b"1.1" } else { version.as_bytes() } }
@@ -0,0 +1,245 @@
+use core::fmt;
+
+use crate::*;
+
+/// The parser detects the message type from the first message.
+pub const MESSAGE_TYPE_AUTODETECT: u8 = 0;
+/// The parser only accepts requests.
+pub const MESSAGE_TYPE_REQUEST: u8 = 1;
+/// The parser only accepts responses.
+pub const MESSAGE_TYPE_RESPONSE: u8 = 2;
+
+/// The parser accepts HTTP messages.
+pub const PROTOCOL_HTTP: u8 = 0;
+/// The parser accepts RTSP/1.0 messages.
+pub const PROTOCOL_RTSP: u8 = 1;
+/// The parser accepts ICE/1.0 messages.
+pub const PROTOCOL_ICE: u8 = 2;
+
+/// A validated parser configuration.
+///
+/// It is a plain value which can be copied into any number of parsers. A
+/// configured parser re-applies it on every `reset`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParserConfig {
+  pub max_start_line_length: usize,
+  pub max_header_length: usize,
+  pub max_body_payload: u64,
+  pub max_unconsumed_length: usize,
+  pub lenient_flags: u64,
+  pub message_type: u8,
+  pub protocol: u8,
+  pub suspend_after_headers: bool,
+  pub manage_unconsumed: bool,
+  pub skip_body: bool,
+  pub debug: bool,
+  pub allow_http10: bool,
+  pub emit_tunnel_data: bool,
+}
+
+/// The reason why a `ParserConfig` was rejected.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+  InvalidMaxStartLineLength = 1,
+  InvalidMaxHeaderLength,
+  InvalidMessageType,
+  UnconsumedLimitWithoutManagement,
+  InvalidLenientFlags,
+  InvalidProtocol,
+}
+
+impl ConfigError {
+  pub fn as_str(&self) -> &str {
+    match self {
+      ConfigError::InvalidMaxStartLineLength => "The maximum start line length must be greater than zero",
+      ConfigError::InvalidMaxHeaderLength => "The maximum header length must be greater than zero",
+      ConfigError::InvalidMessageType => "The message type must be one of the MESSAGE_TYPE_* values",
+      ConfigError::UnconsumedLimitWithoutManagement => {
+        "The maximum unconsumed length requires unconsumed data to be managed"
+      }
+      ConfigError::InvalidLenientFlags => "The lenient flags must be a combination of the LENIENT_* values",
+      ConfigError::InvalidProtocol => "The protocol must be one of the PROTOCOL_* values",
+    }
+  }
+}
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+impl core::error::Error for ConfigError {}
+
+impl Default for ParserConfig {
+  fn default() -> Self {
+    ParserConfig {
+      max_start_line_length: 8192,
+      max_header_length: 8192,
+      max_body_payload: 0,
+      max_unconsumed_length: 0,
+      lenient_flags: LENIENT_NONE,
+      message_type: MESSAGE_TYPE_AUTODETECT,
+      protocol: PROTOCOL_HTTP,
+      suspend_after_headers: false,
+      manage_unconsumed: false,
+      skip_body: false,
+      debug: false,
+      allow_http10: false,
+      emit_tunnel_data: false,
+    }
+  }
+}
+
+impl ParserConfig {
+  /// Creates a builder starting from the default configuration.
+  pub fn builder() -> ParserConfigBuilder { ParserConfigBuilder::default() }
+
+  /// Checks that the configuration values are consistent.
+  pub fn validate(&self) -> Result<(), ConfigError> {
+    if self.max_start_line_length == 0 {
+      return Err(ConfigError::InvalidMaxStartLineLength);
+    }
+
+    if self.max_header_length == 0 {
+      return Err(ConfigError::InvalidMaxHeaderLength);
+    }
+
+    if self.message_type > MESSAGE_TYPE_RESPONSE {
+      return Err(ConfigError::InvalidMessageType);
+    }
+
+    if self.max_unconsumed_length > 0 && !self.manage_unconsumed {
+      return Err(ConfigError::UnconsumedLimitWithoutManagement);
+    }
+
+    if self.lenient_flags & !LENIENT_ALL != 0 {
+      return Err(ConfigError::InvalidLenientFlags);
+    }
+
+    if self.protocol > PROTOCOL_ICE {
+      return Err(ConfigError::InvalidProtocol);
+    }
+
+    Ok(())
+  }
+}
+
+/// A builder for `ParserConfig`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ParserConfigBuilder {
+  config: ParserConfig,
+}
+
+impl ParserConfigBuilder {
+  pub fn max_start_line_length(mut self, value: usize) -> Self {
+    self.config.max_start_line_length = value;
+    self
+  }
+
+  pub fn max_header_length(mut self, value: usize) -> Self {
+    self.config.max_header_length = value;
+    self
+  }
+
+  pub fn max_body_payload(mut self, value: u64) -> Self {
+    self.config.max_body_payload = value;
+    self
+  }
+
+  pub fn max_unconsumed_length(mut self, value: usize) -> Self {
+    self.config.max_unconsumed_length = value;
+    self
+  }
+
+  pub fn lenient_flags(mut self, value: u64) -> Self {
+    self.config.lenient_flags = value;
+    self
+  }
+
+  pub fn message_type(mut self, value: u8) -> Self {
+    self.config.message_type = value;
+    self
+  }
+
+  pub fn protocol(mut self, value: u8) -> Self {
+    self.config.protocol = value;
+    self
+  }
+
+  pub fn suspend_after_headers(mut self, value: bool) -> Self {
+    self.config.suspend_after_headers = value;
+    self
+  }
+
+  pub fn manage_unconsumed(mut self, value: bool) -> Self {
+    self.config.manage_unconsumed = value;
+    self
+  }
+
+  pub fn skip_body(mut self, value: bool) -> Self {
+    self.config.skip_body = value;
+    self
+  }
+
+  pub fn debug(mut self, value: bool) -> Self {
+    self.config.debug = value;
+    self
+  }
+
+  pub fn allow_http10(mut self, value: bool) -> Self {
+    self.config.allow_http10 = value;
+    self
+  }
+
+  pub fn emit_tunnel_data(mut self, value: bool) -> Self {
+    self.config.emit_tunnel_data = value;
+    self
+  }
+
+  /// Validates and returns the configuration.
+  pub fn build(self) -> Result<ParserConfig, ConfigError> {
+    self.config.validate()?;
+    Ok(self.config)
+  }
+}
+
+impl Parser {
+  /// Creates a new parser using the given configuration.
+  pub fn with_config(config: ParserConfig) -> Result<Parser, ConfigError> {
+    let mut parser = Parser::new();
+    parser.apply_config(config)?;
+    Ok(parser)
+  }
+
+  /// Validates and applies a configuration. The configuration is then
+  /// re-applied on every `reset`.
+  pub fn apply_config(&mut self, config: ParserConfig) -> Result<(), ConfigError> {
+    config.validate()?;
+
+    self.config = config;
+    self.has_config = true;
+    self.restore_config();
+    Ok(())
+  }
+
+  /// Copies the stored configuration into the parser fields.
+  pub(crate) fn restore_config(&mut self) {
+    let config = self.config;
+
+    self.max_start_line_length = config.max_start_line_length;
+    self.max_header_length = config.max_header_length;
+    self.max_body_payload = config.max_body_payload;
+    self.max_unconsumed_length = config.max_unconsumed_length;
+    self.lenient_flags = config.lenient_flags;
+    self.autodetect = config.message_type == MESSAGE_TYPE_AUTODETECT;
+    self.is_request = config.message_type == MESSAGE_TYPE_REQUEST;
+    self.protocol = config.protocol;
+    self.suspend_after_headers = config.suspend_after_headers;
+    self.manage_unconsumed = config.manage_unconsumed;
+    self.skip_body = config.skip_body;
+    self.debug = config.debug;
+    self.allow_http10 = config.allow_http10;
+    self.emit_tunnel_data = config.emit_tunnel_data;
+  }
+}
@@ -0,0 +1,140 @@
+use alloc::collections::VecDeque;
+
+use crate::*;
+
+/// A request sent on a `ClientConnection`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SentRequest {
+  /// The sequence number of the request on the connection, starting from `0`.
+  pub id: u64,
+  pub method: Methods,
+}
+
+/// A client side connection pairing responses with the pipelined requests
+/// they answer.
+///
+/// The requests must be registered via `send` in the order they are written.
+/// When the head of a response has been parsed, the parser is configured
+/// according to the request it answers:
+///
+/// * responses to `HEAD` requests skip the body.
+/// * successful responses to `CONNECT` requests enter tunnel mode.
+/// * informational responses (`1xx` except `101`) do not complete the request,
+///   which is answered by the next response.
+///
+/// The parser always suspends after headers, so `parse` must be invoked
+/// again, even without data, while `continue_without_data` is set.
+#[derive(Debug)]
+pub struct ClientConnection {
+  parser: Parser,
+  sent: VecDeque<SentRequest>,
+  current: Option<SentRequest>,
+  next_id: u64,
+  prepared_at: Option<u64>,
+  unpaired: bool,
+}
+
+impl Default for ClientConnection {
+  fn default() -> Self { Self::new() }
+}
+
+impl ClientConnection {
+  /// Creates a new connection with a default parser.
+  pub fn new() -> ClientConnection { Self::with_parser(Parser::new()) }
+
+  /// Creates a new connection using an existing parser.
+  ///
+  /// The parser is set to only parse responses and to suspend after headers.
+  pub fn with_parser(mut parser: Parser) -> ClientConnection {
+    parser.autodetect = false;
+    parser.is_request = false;
+    parser.suspend_after_headers = true;
+
+    ClientConnection {
+      parser,
+      sent: VecDeque::new(),
+      current: None,
+      next_id: 0,
+      prepared_at: None,
+      unpaired: false,
+    }
+  }
+
+  /// Returns the underlying parser.
+  pub fn parser(&self) -> &Parser { &self.parser }
+
+  /// Returns the underlying parser.
+  pub fn parser_mut(&mut self) -> &mut Parser { &mut self.parser }
+
+  /// Consumes the connection, returning the underlying parser.
+  pub fn into_parser(self) -> Parser { self.parser }
+
+  /// Registers a request written on the connection, returning its sequence
+  /// number.
+  pub fn send(&mut self, method: Methods) -> u64 {
+    let id = self.next_id;
+    self.next_id += 1;
+    self.sent.push_back(SentRequest { id, method });
+    id
+  }
+
+  /// Returns the number of requests which have not been answered by a final
+  /// response yet.
+  pub fn pending(&self) -> usize { self.sent.len() }
+
+  /// Returns the request answered by the last response whose head has been
+  /// parsed.
+  pub fn current_request(&self) -> Option<SentRequest> { self.current }
+
+  /// Parses a slice of data, returning the number of consumed bytes.
+  ///
+  /// The events emitted by the parser are available as usual, for instance via
+  /// `Parser::iter_events`. When a response is received while no request is
+  /// pending, the next invocation fails with `UNEXPECTED_RESPONSE`.
+  pub fn parse(&mut self, data: &[u8]) -> usize {
+    // Failing right after the headers would discard the events emitted with them
+    if self.unpaired {
+      self.unpaired = false;
+      self.parser.fail(
+        ERROR_UNEXPECTED_RESPONSE,
+        "Unexpected response without a pending request",
+      );
+      return 0;
+    }
+
+    let consumed = self.parser.parse(data.as_ptr(), data.len());
+
+    // The offset identifies the head, as the parser might have completed a
+    // message and suspended after the headers of the next one
+    if self.parser.state == STATE_BODY_DECISION && self.prepared_at != Some(self.parser.parsed) {
+      self.prepared_at = Some(self.parser.parsed);
+      self.prepare_body();
+    }
+
+    consumed
+  }
+
+  fn prepare_body(&mut self) {
+    let Some(&request) = self.sent.front() else {
+      self.current = None;
+      self.unpaired = true;
+      return;
+    };
+
+    let status = self.parser.status;
+    self.current = Some(request);
+
+    // RFC 9110 section 15.2 - Informational responses precede the final one
+    if status < 200 && status != 101 {
+      return;
+    }
+
+    self.sent.pop_front();
+
+    match request.method {
+      Methods::HEAD => self.parser.skip_body = true,
+      Methods::CONNECT => self.parser.expect_connect_response = true,
+      _ => (),
+    }
+  }
+}
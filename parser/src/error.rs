@@ -0,0 +1,65 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use crate::*;
+
+/// A parsing failure, detached from the parser which reported it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+  /// The error code.
+  pub code: Errors,
+  /// The error description.
+  pub description: String,
+  /// The position of the error in the input of the failed `parse` call.
+  pub position: usize,
+  /// The absolute offset of the error in the whole parsed stream.
+  pub offset: u64,
+  /// The state the parser was in when the error occurred.
+  pub state: States,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} at offset {} (state {}): {}",
+      self.code.as_str(),
+      self.offset,
+      self.state.as_str(),
+      self.description
+    )
+  }
+}
+
+impl core::error::Error for ParseError {}
+
+impl Parser {
+  /// Parses a slice of bytes, returning the number of consumed bytes or the
+  /// error which stopped the parser.
+  pub fn try_parse(&mut self, input: &[u8]) -> Result<usize, ParseError> {
+    let consumed = self.parse(input.as_ptr(), input.len());
+
+    match self.error() {
+      Some(error) => Err(error),
+      None => Ok(consumed),
+    }
+  }
+
+  /// Returns the error of the parser, if it has failed.
+  ///
+  /// Codes which are not one of the `ERROR_*` constants, for instance the ones
+  /// passed to `fail`, are reported as `Errors::USER`.
+  pub fn error(&self) -> Option<ParseError> {
+    if self.state != STATE_ERROR {
+      return None;
+    }
+
+    Some(ParseError {
+      code: Errors::try_from(self.error_code).unwrap_or(Errors::USER),
+      description: self.error_description_str().to_string(),
+      position: self.position,
+      offset: self.error_offset,
+      state: States::try_from(self.error_state).unwrap_or(States::ERROR),
+    })
+  }
+}
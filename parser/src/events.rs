@@ -0,0 +1,189 @@
+use core::slice;
+
+use crate::*;
+
+/// How the body of a message is delimited.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BodyKind {
+  ContentLength,
+  Chunked,
+  None,
+}
+
+/// The payload of an `EVENT_HEADERS` event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HeadersMetadata {
+  pub at: usize,
+  pub status_or_method: u16,
+  pub should_keep_alive: bool,
+  pub should_upgrade: bool,
+  pub has_trailers: bool,
+  pub body_kind: BodyKind,
+  pub content_length: u64,
+  pub version_minor: u8,
+}
+
+/// A decoded parser event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+  /// An event carrying a range of the parsed input.
+  Range { event: Events, at: usize, data: &'a [u8] },
+  /// A header name, with the known header it matches.
+  HeaderName { at: usize, data: &'a [u8], header: Headers },
+  /// A setting decoded from the HTTP2-Settings header, with the 8 characters
+  /// encoding it.
+  Http2Setting {
+    at: usize,
+    data: &'a [u8],
+    identifier: u16,
+    value: u32,
+  },
+  /// The parser changed its state. Only emitted in debug mode.
+  StateChange { at: usize, state: States },
+  /// The headers of a message have been parsed.
+  Headers(HeadersMetadata),
+  /// The parsing failed.
+  Error { at: usize, code: Errors },
+}
+
+/// An iterator over the events emitted by the last parser invocation.
+#[derive(Clone, Debug)]
+pub struct EventIterator<'a> {
+  events: &'a [u8],
+  input: &'a [u8],
+  cursor: usize,
+  consumed: usize,
+}
+
+impl<'a> EventIterator<'a> {
+  /// Returns the number of bytes consumed by the parser invocation which
+  /// emitted the events.
+  pub fn consumed(&self) -> usize { self.consumed }
+}
+
+impl<'a> Iterator for EventIterator<'a> {
+  type Item = Event<'a>;
+
+  fn next(&mut self) -> Option<Event<'a>> {
+    let (event, size) = decode_event(self.events, self.cursor, self.input)?;
+    self.cursor += size;
+    Some(event)
+  }
+}
+
+#[inline(always)]
+fn read_u32(events: &[u8], offset: usize) -> usize {
+  u32::from_le_bytes(events[offset..offset + 4].try_into().unwrap()) as usize
+}
+
+/// Decodes the event at the given cursor, returning it with its payload size.
+#[inline(always)]
+pub(crate) fn decode_event<'a>(events: &[u8], cursor: usize, input: &'a [u8]) -> Option<(Event<'a>, usize)> {
+  let event_type = *events.get(cursor)?;
+
+  match event_type {
+    EVENT_END => None,
+    EVENT_ERROR => {
+      let at = read_u32(events, cursor + 1);
+      let code = Errors::try_from(events[cursor + 5]).ok()?;
+
+      Some((Event::Error { at, code }, 6))
+    }
+    EVENT_HEADERS => {
+      let body_kind = match events[cursor + 10] {
+        0 => BodyKind::ContentLength,
+        1 => BodyKind::Chunked,
+        _ => BodyKind::None,
+      };
+
+      let metadata = HeadersMetadata {
+        at: read_u32(events, cursor + 1),
+        status_or_method: u16::from_le_bytes(events[cursor + 5..cursor + 7].try_into().unwrap()),
+        should_keep_alive: events[cursor + 7] != 0,
+        should_upgrade: events[cursor + 8] != 0,
+        has_trailers: events[cursor + 9] != 0,
+        body_kind,
+        content_length: u64::from_le_bytes(events[cursor + 11..cursor + 19].try_into().unwrap()),
+        version_minor: events[cursor + 19],
+      };
+
+      Some((Event::Headers(metadata), 20))
+    }
+    EVENT_HEADER_NAME => {
+      let at = read_u32(events, cursor + 1);
+      let len = read_u32(events, cursor + 5);
+      let header = Headers::try_from(events[cursor + 9]).ok()?;
+      let data = input.get(at..at + len).unwrap_or(&[]);
+
+      Some((Event::HeaderName { at, data, header }, 10))
+    }
+    EVENT_HTTP2_SETTING => {
+      let at = read_u32(events, cursor + 1);
+      let identifier = u16::from_le_bytes(events[cursor + 5..cursor + 7].try_into().unwrap());
+      let value = read_u32(events, cursor + 7) as u32;
+      let data = input.get(at..at + 8).unwrap_or(&[]);
+
+      Some((
+        Event::Http2Setting {
+          at,
+          data,
+          identifier,
+          value,
+        },
+        11,
+      ))
+    }
+    EVENT_STATE_CHANGE => {
+      let at = read_u32(events, cursor + 1);
+      let state = States::try_from(read_u32(events, cursor + 5) as u8).ok()?;
+
+      Some((Event::StateChange { at, state }, 9))
+    }
+    _ => {
+      let event = Events::try_from(event_type).ok()?;
+      let at = read_u32(events, cursor + 1);
+      let len = read_u32(events, cursor + 5);
+      let data = input.get(at..at + len).unwrap_or(&[]);
+
+      Some((Event::Range { event, at, data }, 9))
+    }
+  }
+}
+
+impl Parser {
+  /// Parses a slice of bytes and returns an iterator over the emitted events.
+  ///
+  /// Only events enabled in `active_events` or `active_callbacks` are
+  /// returned. When `manage_unconsumed` is enabled and unconsumed data was
+  /// prepended, ranges are resolved against the internal carry buffer.
+  pub fn parse_slice<'a>(&'a mut self, input: &'a [u8]) -> EventIterator<'a> {
+    let aggregated = self.manage_unconsumed && self.unconsumed_len > 0 && !self.paused;
+    let limit = input.len() + if aggregated { self.unconsumed_len } else { 0 };
+
+    self.parse(input.as_ptr(), input.len());
+
+    if aggregated {
+      let data = unsafe { slice::from_raw_parts(self.unconsumed_buffer as *const u8, limit) };
+      self.iter_events(data)
+    } else {
+      self.iter_events(input)
+    }
+  }
+
+  /// Returns an iterator over the events emitted by the last `parse` call,
+  /// resolving ranges against the `input` passed to it.
+  pub fn iter_events<'a>(&'a self, input: &'a [u8]) -> EventIterator<'a> {
+    let events: &[u8] = if self.active_events | self.active_callbacks != 0 {
+      unsafe { slice::from_raw_parts(self.events, self.events_capacity) }
+    } else {
+      &[]
+    };
+
+    EventIterator {
+      events,
+      input,
+      cursor: 0,
+      consumed: self.position,
+    }
+  }
+}
@@ -0,0 +1,535 @@
+//! Zero-copy parsing of a complete message head held in a single buffer.
+
+use alloc::string::String;
+use core::str;
+
+use crate::matchers::*;
+use crate::*;
+
+/// A header field borrowed from the parsed buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HeaderRef<'a> {
+  pub name: &'a str,
+  pub value: &'a [u8],
+}
+
+impl HeaderRef<'_> {
+  /// An empty header, useful to initialize the slice passed to `parse_head`.
+  pub const EMPTY: HeaderRef<'static> = HeaderRef { name: "", value: b"" };
+}
+
+/// A message head borrowed from the parsed buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Head<'a> {
+  pub is_request: bool,
+  /// The method, for requests.
+  pub method: Methods,
+  /// The URL, for requests.
+  pub url: &'a [u8],
+  pub version: &'a str,
+  /// The status, for responses.
+  pub status: u32,
+  /// The reason, for responses.
+  pub reason: &'a [u8],
+  /// The number of headers stored at the beginning of the slice passed to
+  /// `parse_head`.
+  pub header_count: usize,
+  /// The number of bytes of the head, including the terminating empty line.
+  pub length: usize,
+}
+
+/// The outcome of `parse_head`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status<T> {
+  /// The head is complete.
+  Complete(T),
+  /// The terminating empty line has not been received yet.
+  Partial,
+}
+
+type LineError = (Errors, &'static str);
+
+// The framing headers received so far, to reject the conflicting ones.
+#[derive(Default)]
+struct Framing {
+  has_content_length: bool,
+  has_transfer_encoding: bool,
+}
+
+/// Parses the head of a request or a response, borrowing all the values from
+/// `data` and storing the headers in `headers`.
+///
+/// The lines are validated by the same matchers of the request line, status
+/// line and header states of the parser, without any parser or events buffer.
+/// The framing headers are checked as in the parser, while the checks done
+/// after the headers, for instance requiring `chunked` as the last transfer
+/// coding, are left to the caller. If `headers` is too short to hold all the
+/// headers, the parsing fails with `TOO_MANY_HEADERS`.
+pub fn parse_head<'b>(data: &'b [u8], headers: &mut [HeaderRef<'b>]) -> Result<Status<Head<'b>>, ParseError> {
+  parse_head_with_config(data, headers, &ParserConfig::default())
+}
+
+/// Parses the head of a message like `parse_head`, honoring the message type,
+/// the protocol, the accepted HTTP versions, the lenient flags and the line
+/// length limits of a parser configuration.
+pub fn parse_head_with_config<'b>(
+  data: &'b [u8],
+  headers: &mut [HeaderRef<'b>],
+  config: &ParserConfig,
+) -> Result<Status<Head<'b>>, ParseError> {
+  let lenient_flags = config.lenient_flags;
+  let protocol = config.protocol;
+  let protocol_name: &[u8] = match protocol {
+    PROTOCOL_RTSP => b"RTSP",
+    PROTOCOL_ICE => b"ICE",
+    _ => b"HTTP",
+  };
+
+  let mut head = Head {
+    is_request: false,
+    method: Methods::OTHER,
+    url: b"",
+    version: "",
+    status: 0,
+    reason: b"",
+    header_count: 0,
+    length: 0,
+  };
+
+  // RFC 9112 section 2.2 - Empty lines before the start line are ignored
+  let mut start = 0;
+  let (cr, terminator) = loop {
+    let line = &data[start..];
+
+    head.is_request = match config.message_type {
+      MESSAGE_TYPE_REQUEST => true,
+      MESSAGE_TYPE_RESPONSE => false,
+      _ => !(line.len() > protocol_name.len() && line[protocol_name.len()] == b'/' && line.starts_with(protocol_name)),
+    };
+
+    let (state, too_long) = if head.is_request {
+      (States::REQUEST_LINE, "Request line too long")
+    } else {
+      (States::STATUS_LINE, "Status line too long")
+    };
+
+    let Some(cr) = find_cr(line, line.len(), lenient_flags) else {
+      if line.len() >= config.max_start_line_length {
+        return Err(error(Errors::UNEXPECTED_CHARACTER, too_long, start, state));
+      }
+
+      return Ok(Status::Partial);
+    };
+
+    match ensure_valid_line(line, cr, line.len(), lenient_flags) {
+      MatchResult::Continue(terminator) if cr == 0 => start += terminator,
+      MatchResult::Continue(terminator) => break (cr, terminator),
+      MatchResult::Suspend => return Ok(Status::Partial),
+      MatchResult::Stop => return Err(error(Errors::UNEXPECTED_CHARACTER, "Expected CRLF", start, state)),
+    }
+  };
+
+  let line = &data[start..];
+  let version_minor = if head.is_request {
+    request_line(line, cr, protocol, protocol_name, config.allow_http10, &mut head)
+      .map_err(|(code, description)| error(code, description, start, States::REQUEST_LINE))?
+  } else {
+    status_line(line, cr, protocol, protocol_name, config.allow_http10, &mut head)
+      .map_err(|(code, description)| error(code, description, start, States::STATUS_LINE))?
+  };
+
+  let mut offset = start + cr + terminator;
+  let mut framing = Framing::default();
+
+  loop {
+    let line = &data[offset..];
+    let available = line.len();
+    let fail = |(code, description): LineError| Err(error(code, description, offset, States::HEADER));
+
+    let cr = match find_header_line_end(line, available, lenient_flags) {
+      HeaderLineScanResult::Cr(cr) => cr,
+      HeaderLineScanResult::Invalid(invalid) => {
+        return match find_char(line, 0, invalid, b':') {
+          Some(_) => fail((Errors::UNEXPECTED_CHARACTER, "Invalid header field value character")),
+          None => fail((Errors::UNEXPECTED_CHARACTER, "Invalid header field name character")),
+        };
+      }
+      HeaderLineScanResult::Incomplete if available >= config.max_header_length => {
+        return fail((Errors::UNEXPECTED_CHARACTER, "Header line too long"));
+      }
+      HeaderLineScanResult::Incomplete => return Ok(Status::Partial),
+    };
+
+    let terminator = match ensure_valid_line(line, cr, available, lenient_flags) {
+      MatchResult::Continue(terminator) => terminator,
+      MatchResult::Suspend => return Ok(Status::Partial),
+      MatchResult::Stop => return fail((Errors::UNEXPECTED_CHARACTER, "Expected CRLF")),
+    };
+
+    if cr == 0 {
+      head.length = offset + terminator;
+      break;
+    }
+
+    // RFC 9112 section 5.2 - Lines starting with whitespace continue the field
+    // value, which is reported as-is, including the folds.
+    let mut line_end = cr;
+    let mut next_line = cr + terminator;
+
+    if lenient_flags & LENIENT_OBS_FOLD != 0 {
+      loop {
+        if next_line == available {
+          if available >= config.max_header_length {
+            return fail((Errors::UNEXPECTED_CHARACTER, "Header line too long"));
+          }
+
+          return Ok(Status::Partial);
+        } else if !is_ws(line[next_line]) {
+          break;
+        }
+
+        let continuation = &line[next_line..];
+        let eol = match find_header_line_end(continuation, available - next_line, lenient_flags) {
+          HeaderLineScanResult::Cr(eol) => eol,
+          HeaderLineScanResult::Invalid(_) => {
+            return fail((Errors::UNEXPECTED_CHARACTER, "Invalid header field value character"));
+          }
+          HeaderLineScanResult::Incomplete if available >= config.max_header_length => {
+            return fail((Errors::UNEXPECTED_CHARACTER, "Header line too long"));
+          }
+          HeaderLineScanResult::Incomplete => return Ok(Status::Partial),
+        };
+
+        match ensure_valid_line(continuation, eol, available - next_line, lenient_flags) {
+          MatchResult::Continue(terminator) => {
+            line_end = next_line + eol;
+            next_line = line_end + terminator;
+          }
+          MatchResult::Suspend => return Ok(Status::Partial),
+          MatchResult::Stop => return fail((Errors::UNEXPECTED_CHARACTER, "Expected CRLF")),
+        }
+      }
+    }
+
+    // RFC 9112 section 5 and RFC 9110 section 5.5 and 5.6
+    let colon = match find_char(line, 0, cr, b':') {
+      Some(index) if index > 0 => index,
+      _ => return fail((Errors::UNEXPECTED_CHARACTER, "Invalid header field name character")),
+    };
+
+    let mut name_end = colon;
+    if lenient_flags & LENIENT_SPACES_BEFORE_COLON != 0 {
+      while name_end > 0 && is_ws(line[name_end - 1]) {
+        name_end -= 1;
+      }
+    }
+
+    if !validate_token(line, 0, name_end) {
+      return fail((Errors::UNEXPECTED_CHARACTER, "Invalid header field name character"));
+    }
+
+    let mut value_start = colon + 1;
+    let mut value_end = line_end;
+    strip_ows(line, &mut value_start, &mut value_end, true);
+
+    let name = &line[..name_end];
+    let value = &line[value_start..value_end];
+
+    if let Err(failure) = framing.check(
+      name,
+      value,
+      line_end != cr,
+      name_end != colon,
+      head.status,
+      version_minor,
+      protocol,
+    ) {
+      return fail(failure);
+    }
+
+    if head.header_count == headers.len() {
+      return fail((Errors::TOO_MANY_HEADERS, "Too many headers"));
+    }
+
+    headers[head.header_count] = HeaderRef {
+      // Header names have been validated as tokens, which are ASCII
+      name: unsafe { str::from_utf8_unchecked(name) },
+      value,
+    };
+
+    head.header_count += 1;
+    offset += next_line;
+  }
+
+  Ok(Status::Complete(head))
+}
+
+// RFC 9112 section 3 - Validates the request line, returning the minor version.
+fn request_line<'b>(
+  line: &'b [u8],
+  cr: usize,
+  protocol: u8,
+  protocol_name: &[u8],
+  allow_http10: bool,
+  head: &mut Head<'b>,
+) -> Result<u8, LineError> {
+  // Length of "GET / HTTP/1.1"
+  if cr < 10 + protocol_name.len() {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Request line too short"));
+  }
+
+  // RFC 9112 section 3.1
+  let method_end = match find_char(line, 0, cr, b' ') {
+    Some(index) if index > 0 => index,
+    _ => return Err((Errors::UNEXPECTED_CHARACTER, "Expected space after method")),
+  };
+
+  // RFC 9112 section 3.2
+  let url_start = method_end + 1;
+  let url_end = match find_char(line, url_start, cr, b' ') {
+    Some(index) if index > url_start => index,
+    _ => return Err((Errors::UNEXPECTED_CHARACTER, "Expected space after URL")),
+  };
+
+  // RFC 9112 section 2.3
+  let protocol_start = url_end + 1;
+  let protocol_end = match find_char(line, protocol_start, cr, b'/') {
+    Some(index) if index > protocol_start => index,
+    _ => return Err((Errors::UNEXPECTED_CHARACTER, "Expected / after the protocol name")),
+  };
+
+  let method = match_method(&line[..method_end], protocol);
+
+  if method == METHOD_OTHER && !validate_token(line, 0, method_end) {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Invalid method character"));
+  }
+
+  if !validate_url(line, url_start, url_end) {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Invalid URL character"));
+  }
+
+  let version_start = protocol_end + 1;
+  if cr != protocol_start + protocol_name.len() + 4 {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Invalid protocol name"));
+  }
+
+  let version = &line[version_start..cr];
+  let valid = if protocol != PROTOCOL_HTTP {
+    if &line[protocol_start..protocol_end] != protocol_name {
+      return Err((Errors::UNEXPECTED_CHARACTER, "Invalid protocol"));
+    } else if version != b"1.0" {
+      return Err((Errors::UNSUPPORTED_HTTP_VERSION, "Unsupported protocol version"));
+    }
+
+    true
+  } else {
+    match &line[protocol_start..cr] {
+      b"HTTP/1.1" => method != METHOD_PRI,
+      b"HTTP/2.0" if method != METHOD_PRI => {
+        return Err((Errors::UNSUPPORTED_HTTP_VERSION, "Unsupported HTTP version"));
+      }
+      b"HTTP/2.0" => true,
+      b"HTTP/1.0" if allow_http10 => method != METHOD_PRI,
+      _ => return Err((Errors::UNEXPECTED_CHARACTER, "Invalid protocol")),
+    }
+  };
+
+  if !valid {
+    return Err((Errors::UNSUPPORTED_HTTP_VERSION, "PRI is only valid with HTTP/2.0"));
+  }
+
+  head.method = Methods::try_from(method).unwrap_or(Methods::OTHER);
+  head.url = &line[url_start..url_end];
+  // The version has been matched against the supported ones, which are ASCII
+  head.version = unsafe { str::from_utf8_unchecked(version) };
+
+  Ok(if version == b"1.0" { 0 } else { 1 })
+}
+
+// RFC 9112 section 4 - Validates the status line, returning the minor version.
+fn status_line<'b>(
+  line: &'b [u8],
+  cr: usize,
+  protocol: u8,
+  protocol_name: &[u8],
+  allow_http10: bool,
+  head: &mut Head<'b>,
+) -> Result<u8, LineError> {
+  // Length of "HTTP/1.1 200 "
+  if cr < 9 + protocol_name.len() {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Status line too short"));
+  }
+
+  let protocol_end = protocol_name.len();
+  let version_start = protocol_end + 1;
+  let version_end = version_start + 3;
+
+  if cr < version_end || line[version_end] != b' ' {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Expected space after protocol"));
+  }
+
+  if protocol != PROTOCOL_HTTP {
+    if &line[..protocol_end] != protocol_name || line[protocol_end] != b'/' {
+      return Err((Errors::UNEXPECTED_CHARACTER, "Invalid protocol"));
+    } else if &line[version_start..version_end] != b"1.0" {
+      return Err((Errors::UNSUPPORTED_HTTP_VERSION, "Unsupported protocol version"));
+    }
+  } else {
+    match &line[..version_end] {
+      b"HTTP/1.1" => {}
+      b"HTTP/1.0" if allow_http10 => {}
+      [b'H', b'T', b'T', b'P', b'/', ..] => {
+        return Err((Errors::UNSUPPORTED_HTTP_VERSION, "Unsupported HTTP version"));
+      }
+      _ => return Err((Errors::UNEXPECTED_CHARACTER, "Invalid protocol")),
+    }
+  }
+
+  // Even if the reason is empty, there must be at least a space after the status
+  let status_start = version_end + 1;
+  if version_end + 5 > cr {
+    return Err((Errors::INVALID_STATUS, "Expected HTTP response status"));
+  }
+
+  if !is_digit(line[status_start]) || !is_digit(line[status_start + 1]) || !is_digit(line[status_start + 2]) {
+    return Err((Errors::INVALID_STATUS, "Invalid HTTP response status"));
+  }
+
+  if line[status_start + 3] != b' ' {
+    return Err((Errors::INVALID_STATUS, "Expected a space after HTTP response status"));
+  }
+
+  let reason_start = status_start + 4;
+  if reason_start != cr && unsafe { !validate_token_value(line.as_ptr().add(reason_start), cr - reason_start) } {
+    return Err((Errors::UNEXPECTED_CHARACTER, "Invalid status reason character"));
+  }
+
+  head.status = ((line[status_start] - b'0') as u32) * 100
+    + ((line[status_start + 1] - b'0') as u32) * 10
+    + (line[status_start + 2] - b'0') as u32;
+  head.reason = &line[reason_start..cr];
+  // The version has been matched against the supported ones, which are ASCII
+  head.version = unsafe { str::from_utf8_unchecked(&line[version_start..version_end]) };
+
+  Ok(
+    if &line[version_start..version_end] == b"1.0" {
+      0
+    } else {
+      1
+    },
+  )
+}
+
+impl Framing {
+  // RFC 9112 section 6 - Rejects the framing headers the parser rejects.
+  #[allow(clippy::too_many_arguments)]
+  fn check(
+    &mut self,
+    name: &[u8],
+    value: &[u8],
+    folded: bool,
+    spaced: bool,
+    status: u32,
+    version_minor: u8,
+    protocol: u8,
+  ) -> Result<(), LineError> {
+    if name.eq_ignore_ascii_case(b"content-length") {
+      if folded {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected obs-fold in Content-Length header",
+        ));
+      } else if spaced {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected whitespace before colon in Content-Length header",
+        ));
+      } else if self.has_transfer_encoding {
+        return Err((
+          Errors::UNEXPECTED_CONTENT_LENGTH,
+          "Unexpected Content-Length header when Transfer-Encoding header is present",
+        ));
+      } else if status == 205 || status == 204 || status / 100 == 1 {
+        return Err((
+          Errors::UNEXPECTED_CONTENT_LENGTH,
+          "Unexpected Content-Length header for a response without body",
+        ));
+      } else if self.has_content_length {
+        return Err((
+          Errors::INVALID_CONTENT_LENGTH,
+          "Invalid duplicate Content-Length header",
+        ));
+      } else if value.is_empty() {
+        return Err((Errors::UNEXPECTED_CHARACTER, "Expected Content-Length header value"));
+      } else if value.len() > 19 || !value.iter().all(|byte| is_digit(*byte)) {
+        return Err((Errors::INVALID_CONTENT_LENGTH, "Invalid Content-Length header"));
+      }
+
+      self.has_content_length = true;
+    } else if name.eq_ignore_ascii_case(b"transfer-encoding") {
+      if folded {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected obs-fold in Transfer-Encoding header",
+        ));
+      } else if spaced {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected whitespace before colon in Transfer-Encoding header",
+        ));
+      } else if self.has_content_length {
+        return Err((
+          Errors::UNEXPECTED_TRANSFER_ENCODING,
+          "Unexpected Transfer-Encoding header when Content-Length header is present",
+        ));
+      } else if status == 304 || status == 205 || status == 204 || status / 100 == 1 {
+        return Err((
+          Errors::UNEXPECTED_TRANSFER_ENCODING,
+          "Unexpected Transfer-Encoding header for a response without body",
+        ));
+      } else if protocol != PROTOCOL_HTTP {
+        return Err((
+          Errors::UNEXPECTED_TRANSFER_ENCODING,
+          "Unexpected Transfer-Encoding header in a RTSP or ICE message",
+        ));
+      } else if version_minor == 0 {
+        return Err((
+          Errors::UNEXPECTED_TRANSFER_ENCODING,
+          "Unexpected Transfer-Encoding header in a HTTP/1.0 message",
+        ));
+      }
+
+      self.has_transfer_encoding = true;
+    } else if name.eq_ignore_ascii_case(b"connection") {
+      if folded {
+        return Err((Errors::UNEXPECTED_CHARACTER, "Unexpected obs-fold in Connection header"));
+      } else if spaced {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected whitespace before colon in Connection header",
+        ));
+      }
+    } else if name.eq_ignore_ascii_case(b"upgrade") {
+      if folded {
+        return Err((Errors::UNEXPECTED_CHARACTER, "Unexpected obs-fold in Upgrade header"));
+      } else if spaced {
+        return Err((
+          Errors::UNEXPECTED_CHARACTER,
+          "Unexpected whitespace before colon in Upgrade header",
+        ));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+fn error(code: Errors, description: &str, position: usize, state: States) -> ParseError {
+  ParseError {
+    code,
+    description: String::from(description),
+    position,
+    offset: position as u64,
+    state,
+  }
+}
@@ -0,0 +1,147 @@
+use alloc::string::String;
+use core::fmt;
+
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::{Method, StatusCode, Uri, Version};
+
+use crate::*;
+
+/// The reason why a parsed message could not be converted to the `http` crate
+/// types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HttpConversionError {
+  /// The method is not representable as `http::Method`.
+  InvalidMethod(Methods),
+  /// The URL is not a valid `http::Uri`.
+  InvalidUri(String),
+  /// The version is not one of the HTTP/1.x versions.
+  InvalidVersion(String),
+  /// The status is not a valid `http::StatusCode`.
+  InvalidStatus(u32),
+  /// A header name is not a valid `http::HeaderName`.
+  InvalidHeaderName(String),
+  /// A header value is not a valid `http::HeaderValue`.
+  InvalidHeaderValue(String),
+}
+
+impl fmt::Display for HttpConversionError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      HttpConversionError::InvalidMethod(method) => write!(f, "Invalid method {}", method.as_str()),
+      HttpConversionError::InvalidUri(uri) => write!(f, "Invalid URI {uri}"),
+      HttpConversionError::InvalidVersion(version) => write!(f, "Invalid HTTP version {version}"),
+      HttpConversionError::InvalidStatus(status) => write!(f, "Invalid status {status}"),
+      HttpConversionError::InvalidHeaderName(name) => write!(f, "Invalid header name {name}"),
+      HttpConversionError::InvalidHeaderValue(name) => write!(f, "Invalid value for header {name}"),
+    }
+  }
+}
+
+impl core::error::Error for HttpConversionError {}
+
+/// Converts a method to a `http::Method`.
+///
+/// `Methods::OTHER` is rejected as the original method name is not known.
+pub fn to_http_method(method: Methods) -> Result<Method, HttpConversionError> {
+  if method == Methods::OTHER {
+    return Err(HttpConversionError::InvalidMethod(method));
+  }
+
+  Method::from_bytes(method.as_str().as_bytes()).map_err(|_| HttpConversionError::InvalidMethod(method))
+}
+
+/// Converts a status to a `http::StatusCode`.
+pub fn to_http_status(status: u32) -> Result<StatusCode, HttpConversionError> {
+  u16::try_from(status)
+    .ok()
+    .and_then(|code| StatusCode::from_u16(code).ok())
+    .ok_or(HttpConversionError::InvalidStatus(status))
+}
+
+/// Converts a version, as reported by the `on_version` event, to a
+/// `http::Version`.
+pub fn to_http_version(version: &str) -> Result<Version, HttpConversionError> {
+  match version {
+    "1.1" => Ok(Version::HTTP_11),
+    "1.0" => Ok(Version::HTTP_10),
+    _ => Err(HttpConversionError::InvalidVersion(version.into())),
+  }
+}
+
+/// Builds a `http::HeaderMap` from pairs of header name and value spans.
+pub fn to_header_map<'a, I>(headers: I) -> Result<HeaderMap, HttpConversionError>
+where
+  I: IntoIterator<Item = (&'a [u8], &'a [u8])>,
+{
+  let mut map = HeaderMap::new();
+
+  for (name, value) in headers {
+    let header_name = HeaderName::from_bytes(name)
+      .map_err(|_| HttpConversionError::InvalidHeaderName(String::from_utf8_lossy(name).into_owned()))?;
+    let header_value = HeaderValue::from_bytes(value)
+      .map_err(|_| HttpConversionError::InvalidHeaderValue(String::from_utf8_lossy(name).into_owned()))?;
+
+    map.append(header_name, header_value);
+  }
+
+  Ok(map)
+}
+
+/// Copies a body chunk, as reported by the `on_data` event, to `Bytes`.
+pub fn to_bytes(data: &[u8]) -> Bytes { Bytes::copy_from_slice(data) }
+
+fn header_map(headers: &[Header]) -> Result<HeaderMap, HttpConversionError> {
+  to_header_map(
+    headers
+      .iter()
+      .map(|header| (header.name.as_bytes(), header.value.as_slice())),
+  )
+}
+
+impl Request {
+  /// Converts the head of the request to `http::request::Parts`.
+  pub fn to_http_parts(&self) -> Result<http::request::Parts, HttpConversionError> {
+    let uri: Uri = self
+      .url
+      .parse()
+      .map_err(|_| HttpConversionError::InvalidUri(self.url.clone()))?;
+
+    let (mut parts, _) = http::Request::new(()).into_parts();
+    // Unknown methods are converted using the received name
+    parts.method = if self.method == Methods::OTHER {
+      Method::from_bytes(self.method_name.as_bytes()).map_err(|_| HttpConversionError::InvalidMethod(self.method))?
+    } else {
+      to_http_method(self.method)?
+    };
+    parts.uri = uri;
+    parts.version = to_http_version(&self.version)?;
+    parts.headers = header_map(&self.headers)?;
+
+    Ok(parts)
+  }
+
+  /// Converts the request to a `http::Request`.
+  pub fn into_http(self) -> Result<http::Request<Bytes>, HttpConversionError> {
+    let parts = self.to_http_parts()?;
+    Ok(http::Request::from_parts(parts, Bytes::from(self.body)))
+  }
+}
+
+impl Response {
+  /// Converts the head of the response to `http::response::Parts`.
+  pub fn to_http_parts(&self) -> Result<http::response::Parts, HttpConversionError> {
+    let (mut parts, _) = http::Response::new(()).into_parts();
+    parts.status = to_http_status(self.status)?;
+    parts.version = to_http_version(&self.version)?;
+    parts.headers = header_map(&self.headers)?;
+
+    Ok(parts)
+  }
+
+  /// Converts the response to a `http::Response`.
+  pub fn into_http(self) -> Result<http::Response<Bytes>, HttpConversionError> {
+    let parts = self.to_http_parts()?;
+    Ok(http::Response::from_parts(parts, Bytes::from(self.body)))
+  }
+}
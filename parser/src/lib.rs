@@ -1,10 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(unused_imports)]
 
 extern crate alloc;
 
 use alloc::ffi::CString;
+use alloc::vec;
 use alloc::vec::Vec;
 use alloc::{boxed::Box, format};
+use core::any::Any;
 use core::cell::{Cell, RefCell};
 use core::ffi::{c_char, c_uchar, c_void};
 use core::fmt::Debug;
@@ -23,6 +26,9 @@ pub struct Parser {
   pub max_start_line_length: usize,
   pub max_header_length: usize,
   pub max_body_payload: u64,
+  pub max_unconsumed_length: usize,
+  pub lenient_flags: u64,
+  pub protocol: u8,
   pub autodetect: bool,
   pub is_request: bool,
   pub suspend_after_headers: bool,
@@ -30,28 +36,46 @@ pub struct Parser {
   pub continue_without_data: bool,
   pub is_connect: bool,
   pub skip_body: bool,
+  pub expect_connect_response: bool,
   pub debug: bool,
+  pub allow_http10: bool,
+  pub emit_tunnel_data: bool,
+
+  // Configuration
+  pub config: ParserConfig,
+  pub has_config: bool,
 
   // Generic state
   pub parsed: u64,
   pub position: usize,
+  pub error_offset: u64,
   pub state: u8,
   pub paused: bool,
   pub error_code: u8,
+  pub error_state: u8,
 
   // Current message flags
   pub content_length: u64,
   pub chunk_size: u64,
   pub remaining_content_length: u64,
   pub remaining_chunk_size: u64,
+  pub tunnel_bytes: u64,
   pub status: u32,
+  pub http2_setting_value: u32,
+  pub http2_setting_identifier: u16,
   pub method: u8,
+  pub known_header: u8,
+  pub version_minor: u8,
   pub has_content_length: bool,
   pub has_transfer_encoding: bool,
   pub has_chunked_transfer_encoding: bool,
   pub has_connection_close: bool,
+  pub has_connection_keep_alive: bool,
   pub has_connection_upgrade: bool,
   pub has_upgrade: bool,
+  pub has_upgrade_h2c: bool,
+  pub has_http2_settings: bool,
+  pub has_connection_http2_settings: bool,
   pub has_trailers: bool,
 
   // Callback handling
@@ -68,10 +92,19 @@ pub struct Parser {
   pub error_description: [u8; 255],
   pub unconsumed: *const c_uchar,
   pub unconsumed_len: usize,
+  pub unconsumed_buffer: *mut c_uchar,
+  pub unconsumed_capacity: usize,
+  pub unconsumed_allocations: u64,
   pub error_description_len: u8,
 
   // Event buffer. Keep this at the end of the struct for external readers.
+  pub owns_events: bool,
+  pub events_capacity: usize,
   pub events: *mut c_uchar,
+
+  // Owned context, only managed by the Rust API. It follows all the fields
+  // read externally so that their offsets do not depend on it.
+  pub owned_context: *mut c_void,
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -90,11 +123,49 @@ generate!();
 
 impl Parser {
   /// Creates a new parser
-  pub fn new() -> Parser {
-    let mut events = Box::new([0u8; 65536]);
-    let events_ptr = events.as_mut_ptr();
-    mem::forget(events);
+  pub fn new() -> Parser { Parser::with_events_capacity(EVENTS_BUFFER_SIZE) }
+
+  /// Creates a new parser with an event buffer of the given capacity.
+  ///
+  /// It panics if the capacity is less than `EVENTS_MIN_BUFFER_SIZE`.
+  pub fn with_events_capacity(capacity: usize) -> Parser {
+    assert!(
+      capacity >= EVENTS_MIN_BUFFER_SIZE,
+      "The events buffer capacity must be at least {} bytes",
+      EVENTS_MIN_BUFFER_SIZE
+    );
+
+    let events = Box::into_raw(vec![0u8; capacity].into_boxed_slice()) as *mut c_uchar;
+    Parser::create(events, capacity, true)
+  }
+
+  /// Creates a new parser using a caller-owned event buffer.
+  ///
+  /// # Panics
+  ///
+  /// It panics if the buffer is null or if the capacity is less than
+  /// `EVENTS_MIN_BUFFER_SIZE`.
+  ///
+  /// # Safety
+  ///
+  /// The buffer must be valid for writes of `capacity` bytes and must outlive
+  /// the parser. The parser never frees it.
+  pub unsafe fn with_events_buffer(events: *mut c_uchar, capacity: usize) -> Parser {
+    assert!(!events.is_null(), "The events buffer must not be null");
+    assert!(
+      capacity >= EVENTS_MIN_BUFFER_SIZE,
+      "The events buffer capacity must be at least {} bytes",
+      EVENTS_MIN_BUFFER_SIZE
+    );
 
+    unsafe {
+      *events = EVENT_END;
+    }
+
+    Parser::create(events, capacity, false)
+  }
+
+  fn create(events: *mut c_uchar, events_capacity: usize, owns_events: bool) -> Parser {
     Parser {
       // User writable
       #[cfg(not(target_family = "wasm"))]
@@ -102,6 +173,9 @@ impl Parser {
       max_start_line_length: 8192,
       max_header_length: 8192,
       max_body_payload: 0,
+      max_unconsumed_length: 0,
+      lenient_flags: LENIENT_NONE,
+      protocol: PROTOCOL_HTTP,
       autodetect: true,
       is_request: false,
       suspend_after_headers: false,
@@ -109,26 +183,43 @@ impl Parser {
       continue_without_data: false,
       is_connect: false,
       skip_body: false,
+      expect_connect_response: false,
       debug: false,
+      allow_http10: false,
+      emit_tunnel_data: false,
+      // Configuration
+      config: ParserConfig::default(),
+      has_config: false,
       // Generic state
       parsed: 0,
       position: 0,
+      error_offset: 0,
       state: STATE_START,
       paused: false,
       error_code: ERROR_NONE,
+      error_state: STATE_START,
       // Current message flags
       content_length: 0,
       chunk_size: 0,
       remaining_content_length: 0,
       remaining_chunk_size: 0,
+      tunnel_bytes: 0,
       status: 0,
+      http2_setting_value: 0,
+      http2_setting_identifier: 0,
       method: 0,
+      known_header: HEADER_OTHER,
+      version_minor: 1,
       has_content_length: false,
       has_transfer_encoding: false,
       has_chunked_transfer_encoding: false,
       has_connection_close: false,
+      has_connection_keep_alive: false,
       has_connection_upgrade: false,
       has_upgrade: false,
+      has_upgrade_h2c: false,
+      has_http2_settings: false,
+      has_connection_http2_settings: false,
       has_trailers: false,
       // Callbacks handling
       active_callbacks: 0,
@@ -142,15 +233,23 @@ impl Parser {
       error_description: [0; 255],
       unconsumed: ptr::null(),
       unconsumed_len: 0,
+      unconsumed_buffer: ptr::null_mut(),
+      unconsumed_capacity: 0,
+      unconsumed_allocations: 0,
       error_description_len: 0,
-      events: events_ptr,
+      owns_events,
+      events_capacity,
+      events,
+      owned_context: ptr::null_mut(),
     }
   }
 
   /// Resets a parser. The second parameters specifies if to also reset the
   /// parsed counter.
   ///
-  /// The following fields are not modified:
+  /// If a configuration has been applied, it is applied again.
+  ///
+  /// Otherwise, the following fields are not modified:
   ///   * position
   ///   * context
   ///   * autodetect
@@ -169,21 +268,23 @@ impl Parser {
     }
 
     self.error_code = ERROR_NONE;
+    self.error_state = STATE_START;
+    self.error_offset = 0;
 
     self.error_description[0] = 0;
     self.error_description_len = 0;
 
-    if self.unconsumed_len > 0 {
-      unsafe {
-        let _ = slice::from_raw_parts(self.unconsumed, self.unconsumed_len);
-      }
-
-      self.unconsumed = ptr::null();
-      self.unconsumed_len = 0;
-    }
+    // The carry buffer is kept for reuse
+    self.unconsumed = ptr::null();
+    self.unconsumed_len = 0;
 
     self.clear();
     self.skip_body = false;
+
+    if self.has_config {
+      self.restore_config();
+    }
+
     unsafe {
       *self.events = EVENT_END;
     }
@@ -192,19 +293,67 @@ impl Parser {
   /// Clears all values about the message in the parser.
   pub fn clear(&mut self) {
     self.is_connect = false;
+    self.expect_connect_response = false;
     self.method = 0;
+    self.known_header = HEADER_OTHER;
+    self.http2_setting_identifier = 0;
+    self.http2_setting_value = 0;
+    self.version_minor = 1;
     self.status = 0;
     self.has_content_length = false;
     self.has_transfer_encoding = false;
     self.has_chunked_transfer_encoding = false;
     self.has_connection_close = false;
+    self.has_connection_keep_alive = false;
     self.has_connection_upgrade = false;
     self.has_upgrade = false;
+    self.has_upgrade_h2c = false;
+    self.has_http2_settings = false;
+    self.has_connection_http2_settings = false;
     self.has_trailers = false;
     self.content_length = 0;
     self.chunk_size = 0;
     self.remaining_content_length = 0;
     self.remaining_chunk_size = 0;
+    self.tunnel_bytes = 0;
+  }
+
+  /// Makes sure the carry buffer can hold at least `needed` bytes, moving the
+  /// first `preserved` bytes of the unconsumed data at its beginning.
+  pub(crate) fn prepare_unconsumed_buffer(&mut self, needed: usize, preserved: usize) {
+    if needed > self.unconsumed_capacity {
+      let mut buffer = Vec::<c_uchar>::with_capacity(needed.next_power_of_two());
+
+      if preserved > 0 {
+        unsafe {
+          ptr::copy_nonoverlapping(self.unconsumed, buffer.as_mut_ptr(), preserved);
+        }
+      }
+
+      self.free_unconsumed_buffer();
+
+      let (buffer, _, capacity) = buffer.into_raw_parts();
+      self.unconsumed_buffer = buffer;
+      self.unconsumed_capacity = capacity;
+      self.unconsumed_allocations += 1;
+    } else if preserved > 0 && !ptr::eq(self.unconsumed, self.unconsumed_buffer) {
+      unsafe {
+        ptr::copy(self.unconsumed, self.unconsumed_buffer, preserved);
+      }
+    }
+
+    self.unconsumed = self.unconsumed_buffer;
+  }
+
+  fn free_unconsumed_buffer(&mut self) {
+    if !self.unconsumed_buffer.is_null() {
+      unsafe {
+        let _ = Vec::from_raw_parts(self.unconsumed_buffer, 0, self.unconsumed_capacity);
+      }
+
+      self.unconsumed_buffer = ptr::null_mut();
+      self.unconsumed_capacity = 0;
+    }
   }
 
   #[inline(always)]
@@ -215,7 +364,7 @@ impl Parser {
     at: usize,
     len: usize,
   ) -> bool {
-    if *event_cursor + 9usize >= EVENTS_BUFFER_SIZE {
+    if *event_cursor + 9usize >= self.events_capacity {
       return false;
     }
 
@@ -230,7 +379,7 @@ impl Parser {
 
   #[inline(always)]
   pub(crate) fn try_emit_event_error(&mut self, event_cursor: &mut usize) -> bool {
-    if *event_cursor + 6usize >= EVENTS_BUFFER_SIZE {
+    if *event_cursor + 6usize >= self.events_capacity {
       return false;
     }
 
@@ -287,29 +436,39 @@ impl Parser {
   /// It always returns zero for internal use.
   #[inline(always)]
   pub fn fail(&mut self, code: u8, description: &str) {
+    let mut event_cursor = 0usize;
+    self.fail_with_events(code, description, &mut event_cursor);
+
+    unsafe {
+      *self.events.add(event_cursor) = EVENT_END;
+    }
+  }
+
+  /// Marks the parsing as failed while appending the error event after the
+  /// events already emitted by the current `parse` invocation.
+  #[inline(always)]
+  pub(crate) fn fail_with_events(&mut self, code: u8, description: &str, event_cursor: &mut usize) {
     let bytes = description.as_bytes();
     let len = bytes.len().min(254);
 
+    self.error_state = self.state;
+    self.error_offset = self.parsed + self.position as u64;
     self.state = STATE_ERROR;
     self.error_code = code;
     self.error_description[..len].copy_from_slice(&bytes[..len]);
     self.error_description[len] = 0;
     self.error_description_len = len as u8;
     let active_events = self.active_events | self.active_callbacks;
-    let mut event_cursor = 0usize;
     if active_events & EVENT_ACTIVE_ON_ERROR != 0 {
-      self.try_emit_event_error(&mut event_cursor);
-    }
-    unsafe {
-      *self.events.add(event_cursor) = EVENT_END;
+      self.try_emit_event_error(event_cursor);
     }
   }
 
   /// Returns the current parser's state as string.
-  pub fn state_str(&self) -> &str { States::try_from(self.state).unwrap().as_str() }
+  pub fn state_str(&self) -> &str { States::try_from(self.state).map_or("UNKNOWN", |state| state.as_str()) }
 
   /// Returns the current parser's error state as string.
-  pub fn error_code_str(&self) -> &str { Errors::try_from(self.error_code).unwrap().as_str() }
+  pub fn error_code_str(&self) -> &str { Errors::try_from(self.error_code).map_or("UNKNOWN", |error| error.as_str()) }
 
   /// Returns the current parser's error description as string.
   pub fn error_description_str(&self) -> &str {
@@ -326,11 +485,67 @@ impl Parser {
   }
 }
 
+impl Parser {
+  /// Sets an owned context, dropping the previous one.
+  ///
+  /// The type of the context is checked at runtime by the accessors, which
+  /// return `None` when a different type is requested. This is independent
+  /// from the raw `context` pointer, which is left untouched.
+  pub fn set_context<C: Any>(&mut self, context: C) {
+    self.drop_context();
+
+    let context: Box<Box<dyn Any>> = Box::new(Box::new(context));
+    self.owned_context = Box::into_raw(context) as *mut c_void;
+  }
+
+  /// Returns the owned context, if it is set and has type `C`.
+  pub fn context<C: Any>(&self) -> Option<&C> {
+    if self.owned_context.is_null() {
+      return None;
+    }
+
+    unsafe { (*(self.owned_context as *const Box<dyn Any>)).downcast_ref::<C>() }
+  }
+
+  /// Returns the owned context mutably, if it is set and has type `C`.
+  pub fn context_mut<C: Any>(&mut self) -> Option<&mut C> {
+    if self.owned_context.is_null() {
+      return None;
+    }
+
+    unsafe { (*(self.owned_context as *mut Box<dyn Any>)).downcast_mut::<C>() }
+  }
+
+  /// Removes and returns the owned context, if it is set and has type `C`.
+  pub fn take_context<C: Any>(&mut self) -> Option<C> {
+    self.context::<C>()?;
+
+    let context = unsafe { Box::from_raw(self.owned_context as *mut Box<dyn Any>) };
+    self.owned_context = ptr::null_mut();
+    context.downcast::<C>().ok().map(|context| *context)
+  }
+
+  fn drop_context(&mut self) {
+    if !self.owned_context.is_null() {
+      unsafe {
+        let _ = Box::from_raw(self.owned_context as *mut Box<dyn Any>);
+      }
+      self.owned_context = ptr::null_mut();
+    }
+  }
+}
+
 impl Drop for Parser {
   fn drop(&mut self) {
+    self.free_unconsumed_buffer();
+
+    self.drop_context();
+
     if !self.events.is_null() {
-      unsafe {
-        let _ = Box::from_raw(self.events as *mut [u8; 65536]);
+      if self.owns_events {
+        unsafe {
+          let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(self.events, self.events_capacity));
+        }
       }
       self.events = ptr::null_mut();
     }
@@ -341,5 +556,34 @@ impl Default for Parser {
   fn default() -> Self { Self::new() }
 }
 
+mod assembler;
+#[cfg(feature = "tokio")]
+mod codec;
+mod config;
+mod connection;
+mod error;
+mod events;
+pub mod head;
+#[cfg(feature = "http")]
+mod interop;
 mod matchers;
 mod parse;
+#[cfg(feature = "std")]
+mod reader;
+mod snapshot;
+mod stream;
+
+pub use crate::assembler::*;
+#[cfg(feature = "tokio")]
+pub use crate::codec::*;
+pub use crate::config::*;
+pub use crate::connection::*;
+pub use crate::error::*;
+pub use crate::events::*;
+pub use crate::head::{parse_head, parse_head_with_config};
+#[cfg(feature = "http")]
+pub use crate::interop::*;
+#[cfg(feature = "std")]
+pub use crate::reader::*;
+pub use crate::snapshot::*;
+pub use crate::stream::*;
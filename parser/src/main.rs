@@ -151,12 +151,28 @@ fn on_header_name(parser: &mut Parser, offset: usize, size: usize) { event(parse
 
 fn on_header_value(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "header_value"); }
 
+fn on_http2_setting(parser: &mut Parser, offset: usize, size: usize) {
+  let (identifier, value) = (parser.http2_setting_identifier, parser.http2_setting_value);
+  append_output(
+    parser,
+    format!("offset={offset} size={size} event=http2_setting identifier={identifier} value={value}"),
+  );
+}
+
 fn on_headers(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "headers"); }
 
 fn on_connect(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "connect"); }
 
 fn on_upgrade(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "upgrade"); }
 
+fn on_upgrade_protocol_name(parser: &mut Parser, offset: usize, size: usize) {
+  event(parser, offset, size, "upgrade_protocol_name");
+}
+
+fn on_upgrade_protocol_version(parser: &mut Parser, offset: usize, size: usize) {
+  event(parser, offset, size, "upgrade_protocol_version");
+}
+
 fn on_chunk_length(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "chunk_length"); }
 
 fn on_chunk_extension_name(parser: &mut Parser, offset: usize, size: usize) {
@@ -173,6 +189,8 @@ fn on_body(parser: &mut Parser, offset: usize, size: usize) { event(parser, offs
 
 fn on_data(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "data"); }
 
+fn on_tunnel_data(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "tunnel_data"); }
+
 fn on_trailer_name(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "trailer_name"); }
 
 fn on_trailer_value(parser: &mut Parser, offset: usize, size: usize) { event(parser, offset, size, "trailer_value"); }
@@ -216,15 +234,19 @@ fn create_parser(mode: Mode) -> Parser {
   parser.callbacks.on_reason = on_reason;
   parser.callbacks.on_header_name = on_header_name;
   parser.callbacks.on_header_value = on_header_value;
+  parser.callbacks.on_http2_setting = on_http2_setting;
   parser.callbacks.on_headers = on_headers;
   parser.callbacks.on_connect = on_connect;
   parser.callbacks.on_upgrade = on_upgrade;
+  parser.callbacks.on_upgrade_protocol_name = on_upgrade_protocol_name;
+  parser.callbacks.on_upgrade_protocol_version = on_upgrade_protocol_version;
   parser.callbacks.on_chunk_length = on_chunk_length;
   parser.callbacks.on_chunk_extension_name = on_chunk_extension_name;
   parser.callbacks.on_chunk_extension_value = on_chunk_extension_value;
   parser.callbacks.on_chunk = on_chunk;
   parser.callbacks.on_body = on_body;
   parser.callbacks.on_data = on_data;
+  parser.callbacks.on_tunnel_data = on_tunnel_data;
   parser.callbacks.on_trailer_name = on_trailer_name;
   parser.callbacks.on_trailer_value = on_trailer_value;
   parser.callbacks.on_trailers = on_trailers;
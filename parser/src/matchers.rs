@@ -1,7 +1,8 @@
-use crate::{QUOTED_PAIR_TABLE, QUOTED_STRING_TABLE, TOKEN_TABLE, URL_TABLE};
+use crate::*;
 
 pub enum MatchResult {
-  Continue,
+  // The line is valid, the value is the length of the line terminator.
+  Continue(usize),
   Suspend,
   Stop,
 }
@@ -13,25 +14,43 @@ pub enum HeaderLineScanResult {
 }
 
 #[inline(always)]
-pub fn ensure_valid_line(data: &[u8], cr: usize, available: usize) -> MatchResult {
-  if cr + 1 == available {
+pub fn ensure_valid_line(data: &[u8], cr: usize, available: usize, lenient_flags: u64) -> MatchResult {
+  if data[cr] == b'\n' {
+    // Only found when LF-only line endings are allowed
+    MatchResult::Continue(1)
+  } else if cr + 1 == available {
     MatchResult::Suspend
-  } else if data[cr + 1] != b'\n' {
-    MatchResult::Stop
+  } else if data[cr + 1] == b'\n' {
+    MatchResult::Continue(2)
+  } else if lenient_flags & LENIENT_OPTIONAL_LF_AFTER_CR != 0 {
+    MatchResult::Continue(1)
   } else {
-    MatchResult::Continue
+    MatchResult::Stop
   }
 }
 
 #[inline(always)]
-pub fn find_cr(data: &[u8], available: usize) -> Option<usize> {
+pub fn find_cr(data: &[u8], available: usize, lenient_flags: u64) -> Option<usize> {
   if available == 0 {
     None
+  } else if lenient_flags & LENIENT_OPTIONAL_CR_BEFORE_LF != 0 {
+    find_char2(data, 0, available - 1, b'\r', b'\n')
   } else {
     find_char(data, 0, available - 1, b'\r')
   }
 }
 
+#[inline(always)]
+pub fn find_header_line_end(data: &[u8], available: usize, lenient_flags: u64) -> HeaderLineScanResult {
+  match scan_header_line(data.as_ptr(), available) {
+    // LF is a control character, so it only ends the line when LF-only line endings are allowed
+    HeaderLineScanResult::Invalid(lf) if data[lf] == b'\n' && lenient_flags & LENIENT_OPTIONAL_CR_BEFORE_LF != 0 => {
+      HeaderLineScanResult::Cr(lf)
+    }
+    result => result,
+  }
+}
+
 #[inline(always)]
 pub fn find_char(buf: &[u8], start: usize, end: usize, needle: u8) -> Option<usize> {
   if start > end || end >= buf.len() {
@@ -50,6 +69,68 @@ pub fn find_char2(buf: &[u8], start: usize, end: usize, needle1: u8, needle2: u8
   memchr::memchr2(needle1, needle2, &buf[start..=end]).map(|i| start + i)
 }
 
+// Returns the id of a method, or `METHOD_OTHER` if it is not known.
+#[inline(always)]
+pub fn match_method(method: &[u8], protocol: u8) -> u8 {
+  if protocol == PROTOCOL_RTSP {
+    // RFC 2326 section 10
+    return match method {
+      b"OPTIONS" => METHOD_OPTIONS,
+      b"DESCRIBE" => METHOD_DESCRIBE,
+      b"ANNOUNCE" => METHOD_ANNOUNCE,
+      b"GET_PARAMETER" => METHOD_GET_PARAMETER,
+      b"PAUSE" => METHOD_PAUSE,
+      b"PLAY" => METHOD_PLAY,
+      b"RECORD" => METHOD_RECORD,
+      b"REDIRECT" => METHOD_REDIRECT,
+      b"SETUP" => METHOD_SETUP,
+      b"SET_PARAMETER" => METHOD_SET_PARAMETER,
+      b"TEARDOWN" => METHOD_TEARDOWN,
+      _ => METHOD_OTHER,
+    };
+  }
+
+  match method.len() {
+    3 => {
+      match method {
+        b"GET" => METHOD_GET,
+        b"PUT" => METHOD_PUT,
+        // The HTTP/2 preface is only recognized when parsing HTTP
+        b"PRI" if protocol == PROTOCOL_HTTP => METHOD_PRI,
+        _ => METHOD_OTHER,
+      }
+    }
+    4 => {
+      match method {
+        b"HEAD" => METHOD_HEAD,
+        b"POST" => METHOD_POST,
+        _ => METHOD_OTHER,
+      }
+    }
+    5 => {
+      match method {
+        b"PATCH" => METHOD_PATCH,
+        b"TRACE" => METHOD_TRACE,
+        _ => METHOD_OTHER,
+      }
+    }
+    6 => {
+      match method {
+        b"DELETE" => METHOD_DELETE,
+        _ => METHOD_OTHER,
+      }
+    }
+    7 => {
+      match method {
+        b"CONNECT" => METHOD_CONNECT,
+        b"OPTIONS" => METHOD_OPTIONS,
+        _ => METHOD_OTHER,
+      }
+    }
+    _ => METHOD_OTHER,
+  }
+}
+
 #[inline(always)]
 pub fn is_digit(byte: u8) -> bool { byte.wrapping_sub(b'0') <= 9 }
 
@@ -74,6 +155,34 @@ pub fn validate_token(data: &[u8], start: usize, end: usize) -> bool {
   true
 }
 
+// RFC 4648 section 5 - Returns the value of a base64url character.
+#[inline(always)]
+pub fn decode_base64url(byte: u8) -> Option<u32> {
+  match byte {
+    b'A'..=b'Z' => Some((byte - b'A') as u32),
+    b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+    b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+    b'-' => Some(62),
+    b'_' => Some(63),
+    _ => None,
+  }
+}
+
+// RFC 7540 section 6.5.1 - Decodes a setting from 8 base64url characters (6
+// bytes).
+#[inline(always)]
+pub fn decode_http2_setting(data: &[u8], start: usize) -> Option<(u16, u32)> {
+  let mut decoded = 0u64;
+
+  let mut i = start;
+  while i < start + 8 {
+    decoded = (decoded << 6) | decode_base64url(data[i])? as u64;
+    i += 1;
+  }
+
+  Some(((decoded >> 32) as u16, decoded as u32))
+}
+
 #[inline(always)]
 pub fn validate_quoted_string(data: &[u8], start: usize, end: usize) -> bool {
   let mut i = start;
@@ -168,7 +277,7 @@ fn scan_header_line_scalar(ptr: *const u8, mut i: usize, end: usize) -> HeaderLi
 
 #[cfg(target_arch = "aarch64")]
 #[inline(always)]
-pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult {
+fn scan_header_line(ptr: *const u8, len: usize) -> HeaderLineScanResult {
   use core::arch::aarch64::*;
 
   if len < 16 {
@@ -205,7 +314,7 @@ pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult
 
 #[cfg(target_arch = "x86_64")]
 #[inline(always)]
-pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult {
+fn scan_header_line(ptr: *const u8, len: usize) -> HeaderLineScanResult {
   use core::arch::x86_64::*;
 
   if len < 16 {
@@ -297,7 +406,7 @@ pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult
 
 #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
 #[target_feature(enable = "simd128")]
-pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult {
+fn scan_header_line(ptr: *const u8, len: usize) -> HeaderLineScanResult {
   use core::arch::wasm32::*;
 
   if len < 16 {
@@ -338,7 +447,7 @@ pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult
   all(target_arch = "wasm32", target_feature = "simd128")
 )))]
 #[inline(always)]
-pub fn find_header_line_end(ptr: *const u8, len: usize) -> HeaderLineScanResult { scan_header_line_scalar(ptr, 0, len) }
+fn scan_header_line(ptr: *const u8, len: usize) -> HeaderLineScanResult { scan_header_line_scalar(ptr, 0, len) }
 
 #[cfg(target_arch = "aarch64")]
 #[target_feature(enable = "neon")]
@@ -1,12 +1,18 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_uchar};
 use core::ptr;
 use core::str;
 use core::{slice, slice::from_raw_parts};
-use std::ffi::{CString, c_char, c_uchar};
 
 use crate::parse;
-use crate::{Callbacks, Errors, Events, Methods, Parser, States};
+use crate::{
+  Callbacks, EVENTS_MIN_BUFFER_SIZE, Errors, Events, Headers, Methods, PROTOCOL_ICE, Parser, ParserConfig,
+  SnapshotError, States,
+};
 
 #[repr(C)]
 pub struct CStringWithLength {
@@ -35,6 +41,23 @@ impl From<CStringWithLength> for &str {
   }
 }
 
+#[repr(C)]
+pub struct BufferWithLength {
+  pub ptr: *const c_uchar,
+  pub len: usize,
+}
+
+impl From<Vec<u8>> for BufferWithLength {
+  fn from(value: Vec<u8>) -> Self {
+    let len = value.len();
+
+    BufferWithLength {
+      ptr: Box::into_raw(value.into_boxed_slice()) as *const c_uchar,
+      len,
+    }
+  }
+}
+
 /// Returns if debug informations are available in this build.
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_has_debug() -> bool { cfg!(any(debug_assertions, feature = "debug")) }
@@ -59,10 +82,67 @@ pub extern "C" fn milo_free_string(s: CStringWithLength) {
   }
 }
 
+/// Cleans up memory used by a buffer previously returned by one of the milo's C
+/// public interface.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_free_buffer(buffer: BufferWithLength) {
+  if buffer.ptr.is_null() {
+    return;
+  }
+
+  unsafe {
+    let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.ptr as *mut c_uchar, buffer.len));
+  }
+}
+
 /// Creates a new parser.
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_create() -> *mut Parser { Box::into_raw(Box::new(Parser::new())) }
 
+/// Creates a new parser using a caller-owned event buffer.
+///
+/// The buffer must outlive the parser, which never frees it. It returns a null
+/// pointer if the buffer is null or its capacity is less than
+/// `EVENTS_MIN_BUFFER_SIZE`.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_create_with_buffer(buffer: *mut c_uchar, capacity: usize) -> *mut Parser {
+  if buffer.is_null() || capacity < EVENTS_MIN_BUFFER_SIZE {
+    return ptr::null_mut();
+  }
+
+  Box::into_raw(Box::new(unsafe { Parser::with_events_buffer(buffer, capacity) }))
+}
+
+/// Returns the default parser configuration.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_config_default() -> ParserConfig { ParserConfig::default() }
+
+/// Validates a parser configuration. It returns `0` if the configuration is
+/// valid, otherwise the `ConfigError` value.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_config_validate(config: *const ParserConfig) -> u8 {
+  unsafe { (*config).validate().map_or_else(|error| error as u8, |_| 0) }
+}
+
+/// Creates a new parser using a configuration.
+///
+/// It returns a null pointer if the configuration is not valid.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_create_with_config(config: *const ParserConfig) -> *mut Parser {
+  match Parser::with_config(unsafe { *config }) {
+    Ok(parser) => Box::into_raw(Box::new(parser)),
+    Err(_) => ptr::null_mut(),
+  }
+}
+
+/// Applies a configuration to a parser, which is then re-applied on every
+/// reset. It returns `0` if the configuration is valid, otherwise the
+/// `ConfigError` value.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_apply_config(parser: *mut Parser, config: *const ParserConfig) -> u8 {
+  unsafe { (*parser).apply_config(*config).map_or_else(|error| error as u8, |_| 0) }
+}
+
 /// Destroys a parser.
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_destroy(parser: *mut Parser) {
@@ -75,6 +155,28 @@ pub extern "C" fn milo_destroy(parser: *mut Parser) {
   }
 }
 
+/// Serializes the state of a parser.
+///
+/// The returned value must be freed using `free_buffer`.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_snapshot(parser: *const Parser) -> BufferWithLength { unsafe { (*parser).snapshot().into() } }
+
+/// Restores the state of a parser from a snapshot. It returns `0` if the
+/// snapshot was restored, otherwise the `SnapshotError` value. A null `data`
+/// pointer is reported as `InvalidMagic`.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_restore(parser: *mut Parser, data: *const c_uchar, len: usize) -> u8 {
+  if data.is_null() {
+    return SnapshotError::InvalidMagic as u8;
+  }
+
+  unsafe {
+    (*parser)
+      .restore(from_raw_parts(data, len))
+      .map_or_else(|error| error as u8, |_| 0)
+  }
+}
+
 /// Parses a slice of characters. It returns the number of consumed characters.
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_parse(parser: *mut Parser, data: *const c_uchar, limit: usize) -> usize {
@@ -97,6 +199,40 @@ pub extern "C" fn milo_set_max_body_payload(parser: *mut Parser, value: u64) {
   }
 }
 
+/// Sets the maximum length of the unconsumed data retained between parse
+/// invocations.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_max_unconsumed_length(parser: *mut Parser, value: usize) {
+  unsafe {
+    (*parser).max_unconsumed_length = value;
+  }
+}
+
+/// Sets the lenient parsing flags. Set to one or more `LENIENT_*` values.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_lenient_flags(parser: *mut Parser, value: u64) {
+  unsafe {
+    (*parser).lenient_flags = value;
+  }
+}
+
+/// Sets the accepted protocol. Set to one of the `PROTOCOL_*` values.
+///
+/// It returns `false` and leaves the protocol unchanged if the value is not
+/// valid.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_protocol(parser: *mut Parser, value: u8) -> bool {
+  if value > PROTOCOL_ICE {
+    return false;
+  }
+
+  unsafe {
+    (*parser).protocol = value;
+  }
+
+  true
+}
+
 /// Sets whether parsing should stop after headers have completed.
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_set_suspend_after_headers(parser: *mut Parser, value: bool) {
@@ -105,6 +241,30 @@ pub extern "C" fn milo_set_suspend_after_headers(parser: *mut Parser, value: boo
   }
 }
 
+/// Sets whether HTTP/1.0 messages are accepted.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_allow_http10(parser: *mut Parser, value: bool) {
+  unsafe {
+    (*parser).allow_http10 = value;
+  }
+}
+
+/// Sets whether the data following a tunnel should be consumed and reported.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_emit_tunnel_data(parser: *mut Parser, value: bool) {
+  unsafe {
+    (*parser).emit_tunnel_data = value;
+  }
+}
+
+/// Sets whether the next response answers a `CONNECT` request.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_set_expect_connect_response(parser: *mut Parser, value: bool) {
+  unsafe {
+    (*parser).expect_connect_response = value;
+  }
+}
+
 /// Resets a parser. The second parameters specifies if to also reset the
 /// parsed counter.
 ///
@@ -115,6 +275,9 @@ pub extern "C" fn milo_set_suspend_after_headers(parser: *mut Parser, value: boo
 ///   * is_request
 ///   * suspend_after_headers
 ///   * max_body_payload
+///   * max_unconsumed_length
+///   * lenient_flags
+///   * protocol
 ///   * manage_unconsumed
 ///   * continue_without_data
 ///   * debug
@@ -140,6 +303,14 @@ pub extern "C" fn milo_resume(parser: *mut Parser) { unsafe { (*parser).resume()
 #[unsafe(no_mangle)]
 pub extern "C" fn milo_complete(parser: *mut Parser) { unsafe { (*parser).complete() } }
 
+/// Leaves the tunnel state, parsing the buffered data as a new message.
+///
+/// It returns the number of consumed characters. Without `manage_unconsumed`
+/// it returns `0` and the data not consumed by the last `milo_parse` call
+/// must be passed to it again.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_cancel_tunnel(parser: *mut Parser) -> usize { unsafe { (*parser).cancel_tunnel() } }
+
 /// Marks the parser as finished. Any new data received via `milo_parse` will
 /// put the parser in the error state.
 #[unsafe(no_mangle)]
@@ -169,6 +340,16 @@ pub extern "C" fn milo_method_to_string(method: u8) -> CStringWithLength {
     .into()
 }
 
+/// Returns a known header as string.
+///
+/// The returned value must be freed using `free_string`.
+#[unsafe(no_mangle)]
+pub extern "C" fn milo_header_to_string(header: u8) -> CStringWithLength {
+  Headers::try_from(header)
+    .map_or("UNKNOWN", |header| header.as_str())
+    .into()
+}
+
 /// Returns a parser error as string.
 ///
 /// The returned value must be freed using `free_string`.
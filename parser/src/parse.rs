@@ -10,8 +10,11 @@ use core::fmt::Debug;
 use core::ptr;
 use core::str;
 use core::{slice, slice::from_raw_parts};
-use std::string;
-#[cfg(all(not(target_family = "wasm"), any(debug_assertions, feature = "debug")))]
+#[cfg(all(
+  feature = "std",
+  not(target_family = "wasm"),
+  any(debug_assertions, feature = "debug")
+))]
 use std::time::Instant;
 
 use milo_macros::*;
@@ -44,18 +47,19 @@ impl Parser {
     // Set the data to analyze, prepending unconsumed data from previous iteration
     // if needed
     let mut limit = limit;
-    let aggregate: Vec<c_uchar>;
     let unconsumed_len = self.unconsumed_len;
+    let aggregated = self.manage_unconsumed && unconsumed_len > 0;
 
-    let mut data = if self.manage_unconsumed && unconsumed_len > 0 {
-      unsafe {
-        limit += unconsumed_len;
-        let unconsumed = from_raw_parts(self.unconsumed, unconsumed_len);
+    let mut data = if aggregated {
+      limit += unconsumed_len;
+
+      // Rebuild a contiguous view in the carry buffer so state handlers can scan
+      // across parse-call boundaries.
+      self.prepare_unconsumed_buffer(limit, unconsumed_len);
 
-        // Rebuild a contiguous view so state handlers can scan across parse-call
-        // boundaries.
-        aggregate = [unconsumed, input].concat();
-        &aggregate[..]
+      unsafe {
+        ptr::copy_nonoverlapping(input.as_ptr(), self.unconsumed_buffer.add(unconsumed_len), input.len());
+        from_raw_parts(self.unconsumed_buffer as *const c_uchar, limit)
       }
     } else {
       input
@@ -66,16 +70,24 @@ impl Parser {
 
     let mut available = data.len();
 
-    #[cfg(all(not(target_family = "wasm"), any(debug_assertions, feature = "debug")))]
+    #[cfg(all(
+      feature = "std",
+      not(target_family = "wasm"),
+      any(debug_assertions, feature = "debug")
+    ))]
     let mut last = Instant::now();
 
-    #[cfg(all(not(target_family = "wasm"), any(debug_assertions, feature = "debug")))]
+    #[cfg(all(
+      feature = "std",
+      not(target_family = "wasm"),
+      any(debug_assertions, feature = "debug")
+    ))]
     let start = Instant::now();
 
     #[cfg(any(debug_assertions, feature = "debug"))]
     let mut previous_state = self.state;
 
-    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg(all(feature = "std", any(debug_assertions, feature = "debug")))]
     let previous_position = self.position;
 
     // States will advance position manually, the parser has to explicitly
@@ -84,6 +96,13 @@ impl Parser {
     let mut advanced: usize;
     let mut parsing = true;
     let max_body_payload = self.max_body_payload;
+    let lenient_flags = self.lenient_flags;
+    let protocol = self.protocol;
+    let protocol_name: &[u8] = match protocol {
+      PROTOCOL_RTSP => b"RTSP",
+      PROTOCOL_ICE => b"ICE",
+      _ => b"HTTP",
+    };
     let mut body_payload_read = 0u64;
     let has_active_events = active_events != 0;
     let (
@@ -122,14 +141,14 @@ impl Parser {
       )
     };
 
-    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg(all(feature = "std", any(debug_assertions, feature = "debug")))]
     if self.debug {
       eprintln!("[milo_parser::debug] loop enter");
     }
 
     // Until there is data or there is a request to continue
     'parser: while parsing && (!self.paused) && (available != 0 || self.continue_without_data) {
-      #[cfg(any(debug_assertions, feature = "debug"))]
+      #[cfg(all(feature = "std", any(debug_assertions, feature = "debug")))]
       if self.debug {
         eprintln!(
           "[milo_parser::debug] loop before processing: previous_position={}, position={}, available={}, \
@@ -138,6 +157,11 @@ impl Parser {
         );
       }
 
+      // Make sure the events of a single iteration are never partially emitted.
+      if has_active_events && event_cursor + EVENTS_MAX_STEP_SIZE >= self.events_capacity {
+        break 'parser;
+      }
+
       // Reset the flag before processing; states set it again when they need another
       // zero-byte turn.
       self.continue_without_data = false;
@@ -169,7 +193,10 @@ impl Parser {
                 event_with_range!(on_message_start, 0, 0);
               }
               move_to!(status_line);
-            } else if data.len() >= 5 && data[4] == b'/' && data.starts_with(b"HTTP") {
+            } else if data.len() > protocol_name.len()
+              && data[protocol_name.len()] == b'/'
+              && data.starts_with(protocol_name)
+            {
               self.is_request = false;
               if has_response_start_events {
                 event_with_range!(on_response, 0, 0);
@@ -192,24 +219,24 @@ impl Parser {
           }
 
           STATE_REQUEST_LINE => {
-            match find_cr(data, available) {
+            match find_cr(data, available, lenient_flags) {
               // // RFC 9112 section 3
               Some(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 // RFC 9112 section 2.2 - Repeated
                 if cr == 0 {
-                  advance!(2);
+                  advance!(terminator);
                   next!();
-                } else if cr < 14
+                } else if cr < 10 + protocol_name.len()
                 // Length of "GET / HTTP/1.1"
                 {
                   fail!(UNEXPECTED_CHARACTER, "Request line too short");
@@ -245,48 +272,10 @@ impl Parser {
                   }
                 };
 
-                let method_slice = &data[method_start..method_end];
-                self.method = match method_slice.len() {
-                  3 => {
-                    match method_slice {
-                      b"GET" => METHOD_GET,
-                      b"PUT" => METHOD_PUT,
-                      b"PRI" => METHOD_PRI,
-                      _ => METHOD_OTHER,
-                    }
-                  }
-                  4 => {
-                    match method_slice {
-                      b"HEAD" => METHOD_HEAD,
-                      b"POST" => METHOD_POST,
-                      _ => METHOD_OTHER,
-                    }
-                  }
-                  5 => {
-                    match method_slice {
-                      b"PATCH" => METHOD_PATCH,
-                      b"TRACE" => METHOD_TRACE,
-                      _ => METHOD_OTHER,
-                    }
-                  }
-                  6 => {
-                    match method_slice {
-                      b"DELETE" => METHOD_DELETE,
-                      _ => METHOD_OTHER,
-                    }
-                  }
-                  7 => {
-                    match method_slice {
-                      b"CONNECT" => {
-                        self.is_connect = true;
-                        METHOD_CONNECT
-                      }
-                      b"OPTIONS" => METHOD_OPTIONS,
-                      _ => METHOD_OTHER,
-                    }
-                  }
-                  _ => METHOD_OTHER,
-                };
+                self.method = match_method(&data[method_start..method_end], protocol);
+                if self.method == METHOD_CONNECT {
+                  self.is_connect = true;
+                }
 
                 if self.method == METHOD_OTHER && !validate_token(data, method_start, method_end) {
                   fail!(UNEXPECTED_CHARACTER, "Invalid method character");
@@ -297,11 +286,19 @@ impl Parser {
                 }
 
                 let version_start = protocol_end + 1;
-                if cr != protocol_start + 8 {
+                if cr != protocol_start + protocol_name.len() + 4 {
                   fail!(UNEXPECTED_CHARACTER, "Invalid protocol name");
                 }
 
-                if &data[protocol_start..cr] == b"HTTP/1.1" {
+                if protocol != PROTOCOL_HTTP {
+                  if &data[protocol_start..protocol_end] != protocol_name {
+                    fail!(UNEXPECTED_CHARACTER, "Invalid protocol");
+                  } else if &data[version_start..cr] != b"1.0" {
+                    fail!(UNSUPPORTED_HTTP_VERSION, "Unsupported protocol version");
+                  }
+
+                  self.version_minor = 0;
+                } else if &data[protocol_start..cr] == b"HTTP/1.1" {
                   if self.method == METHOD_PRI {
                     fail!(UNSUPPORTED_HTTP_VERSION, "PRI is only valid with HTTP/2.0");
                   }
@@ -309,6 +306,12 @@ impl Parser {
                   if self.method != METHOD_PRI {
                     fail!(UNSUPPORTED_HTTP_VERSION, "Unsupported HTTP version");
                   }
+                } else if self.allow_http10 && &data[protocol_start..cr] == b"HTTP/1.0" {
+                  if self.method == METHOD_PRI {
+                    fail!(UNSUPPORTED_HTTP_VERSION, "PRI is only valid with HTTP/2.0");
+                  }
+
+                  self.version_minor = 0;
                 } else {
                   fail!(UNEXPECTED_CHARACTER, "Invalid protocol");
                 }
@@ -320,7 +323,7 @@ impl Parser {
                   event_with_range!(on_version, version_start, 3);
                 }
 
-                advance!(cr + 2);
+                advance!(cr + terminator);
 
                 if self.method == METHOD_PRI {
                   move_to!(http2_preface);
@@ -340,47 +343,63 @@ impl Parser {
 
           // RFC 9112 section 4
           STATE_STATUS_LINE => {
-            match find_cr(data, available) {
+            match find_cr(data, available, lenient_flags) {
               Some(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 // RFC 9112 section 2.2 - Repeated
                 if cr == 0 {
-                  advance!(2);
+                  advance!(terminator);
                   next!();
-                } else if cr < 13
+                } else if cr < 9 + protocol_name.len()
                 // Length of "HTTP/1.1 200 "
                 {
                   fail!(UNEXPECTED_CHARACTER, "Status line too short");
                 }
 
-                // The line is potentially valid, clear the parser
+                // The line is potentially valid, clear the parser, but keep
+                // the expectation set by the caller for this response
+                let expect_connect_response = self.expect_connect_response;
                 self.clear();
+                self.expect_connect_response = expect_connect_response;
 
                 let protocol_start = 0;
-                let protocol_end = 4;
+                let protocol_end = protocol_name.len();
                 let version_start = protocol_end + 1;
-                let version_end = protocol_start + 8;
+                let version_end = version_start + 3;
 
                 if cr < version_end || data[version_end] != b' ' {
                   fail!(UNEXPECTED_CHARACTER, "Expected space after protocol");
                 }
 
-                match &data[protocol_start..version_end] {
-                  b"HTTP/1.1" => {}
-                  [b'H', b'T', b'T', b'P', b'/', ..] => {
-                    fail!(UNSUPPORTED_HTTP_VERSION, "Unsupported HTTP version");
-                  }
-                  _ => {
+                if protocol != PROTOCOL_HTTP {
+                  if &data[protocol_start..protocol_end] != protocol_name || data[protocol_end] != b'/' {
                     fail!(UNEXPECTED_CHARACTER, "Invalid protocol");
+                  } else if &data[version_start..version_end] != b"1.0" {
+                    fail!(UNSUPPORTED_HTTP_VERSION, "Unsupported protocol version");
+                  }
+
+                  self.version_minor = 0;
+                } else {
+                  match &data[protocol_start..version_end] {
+                    b"HTTP/1.1" => {}
+                    b"HTTP/1.0" if self.allow_http10 => {
+                      self.version_minor = 0;
+                    }
+                    [b'H', b'T', b'T', b'P', b'/', ..] => {
+                      fail!(UNSUPPORTED_HTTP_VERSION, "Unsupported HTTP version");
+                    }
+                    _ => {
+                      fail!(UNEXPECTED_CHARACTER, "Invalid protocol");
+                    }
                   }
                 }
 
@@ -416,7 +435,7 @@ impl Parser {
                   + (data[status_start + 2] - b'0') as u32;
 
                 if has_response_line_events {
-                  event_with_range!(on_protocol, protocol_start, 4);
+                  event_with_range!(on_protocol, protocol_start, protocol_end);
                   event_with_range!(on_version, version_start, 3);
                   event_with_range!(on_status, status_start, 3);
                   if reason_end > reason_start {
@@ -424,7 +443,7 @@ impl Parser {
                   }
                 }
 
-                advance!(cr + 2);
+                advance!(cr + terminator);
                 move_to!(header);
               }
               None => {
@@ -449,23 +468,29 @@ impl Parser {
           }
 
           STATE_HEADER => {
-            match find_header_line_end(data.as_ptr(), available) {
+            match find_header_line_end(data, available, lenient_flags) {
               HeaderLineScanResult::Cr(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 // No more headers or no headers at all, move to the headers state
                 if cr == 0 {
-                  advance!(2);
+                  // RFC 9112 section 9.3 - HTTP/1.0 connections only persist when requested.
+                  // RTSP connections persist regardless of the version.
+                  if self.version_minor == 0 && protocol != PROTOCOL_RTSP && !self.has_connection_keep_alive {
+                    self.has_connection_close = true;
+                  }
+
+                  advance!(terminator);
                   if has_metadata_event {
-                    event_with_metadata!(on_headers, 2);
+                    event_with_metadata!(on_headers, terminator);
                   }
                   move_to!(body_decision);
                   self.continue_without_data = true;
@@ -477,21 +502,90 @@ impl Parser {
                   }
                 }
 
+                // RFC 9112 section 5.2 - Lines starting with whitespace continue the field
+                // value. The value is not unfolded: it is reported as-is, including the folds,
+                // since replacing them would require copying the data.
+                let mut line_end = cr;
+                let mut next_line = cr + terminator;
+
+                if lenient_flags & LENIENT_OBS_FOLD != 0 {
+                  loop {
+                    if next_line == available {
+                      if available >= self.max_header_length {
+                        fail!(UNEXPECTED_CHARACTER, "Header line too long");
+                      } else {
+                        suspend!();
+                      }
+                    } else if !is_ws(data[next_line]) {
+                      break;
+                    }
+
+                    let continuation = &data[next_line..];
+                    match find_header_line_end(continuation, available - next_line, lenient_flags) {
+                      HeaderLineScanResult::Cr(eol) => {
+                        match ensure_valid_line(continuation, eol, available - next_line, lenient_flags) {
+                          MatchResult::Continue(terminator) => {
+                            line_end = next_line + eol;
+                            next_line = line_end + terminator;
+                          }
+                          MatchResult::Suspend => {
+                            suspend!();
+                          }
+                          MatchResult::Stop => {
+                            fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
+                          }
+                        }
+                      }
+                      HeaderLineScanResult::Invalid(_) => {
+                        fail!(UNEXPECTED_CHARACTER, "Invalid header field value character");
+                      }
+                      HeaderLineScanResult::Incomplete => {
+                        if available >= self.max_header_length {
+                          fail!(UNEXPECTED_CHARACTER, "Header line too long");
+                        } else {
+                          suspend!();
+                        }
+                      }
+                    }
+                  }
+                }
+
+                let folded = line_end != cr;
+
                 // RFC 9112 section.4
                 // RFC 9110 section 5.5 and 5.6
                 let header_name_start = 0;
-                let header_name_end = match find_char(data, header_name_start, cr, b':') {
+                let colon = match find_char(data, header_name_start, cr, b':') {
                   Some(index) if index > header_name_start => index,
                   _ => {
                     fail!(UNEXPECTED_CHARACTER, "Invalid header field name character");
                   }
                 };
-                let mut header_value_start = header_name_end + 1;
-                let mut header_value_end = cr;
+
+                let mut header_name_end = colon;
+                if lenient_flags & LENIENT_SPACES_BEFORE_COLON != 0 {
+                  while header_name_end > header_name_start && is_ws(data[header_name_end - 1]) {
+                    header_name_end -= 1;
+                  }
+
+                  if header_name_end == header_name_start {
+                    fail!(UNEXPECTED_CHARACTER, "Invalid header field name character");
+                  }
+                }
+
+                // Whitespace before the colon is never accepted in framing headers
+                let spaced = header_name_end != colon;
+
+                let mut header_value_start = colon + 1;
+                let mut header_value_end = line_end;
 
                 let status = self.status;
+                let mut http2_settings = None;
+                let mut upgrade_protocols = None;
+                // Set when matching the framing headers, so the name is not matched twice
+                let mut header = None;
                 let first_header_byte = data[header_name_start];
-                if !matches!(first_header_byte, b'c' | b'C' | b't' | b'T' | b'u' | b'U') {
+                if !matches!(first_header_byte, b'c' | b'C' | b'h' | b'H' | b't' | b'T' | b'u' | b'U') {
                   if !validate_token(data, header_name_start, header_name_end) {
                     fail!(UNEXPECTED_CHARACTER, "Invalid header field name character");
                   }
@@ -504,6 +598,17 @@ impl Parser {
                   match (header_name_len, &data[header_name_start..header_name_end]) {
                     // RFC 9112 section 6.2
                     (14, case_insensitive_string!("content-length")) => {
+                      header = Some(HEADER_CONTENT_LENGTH);
+
+                      if folded {
+                        fail!(UNEXPECTED_CHARACTER, "Unexpected obs-fold in Content-Length header");
+                      } else if spaced {
+                        fail!(
+                          UNEXPECTED_CHARACTER,
+                          "Unexpected whitespace before colon in Content-Length header"
+                        );
+                      }
+
                       if self.has_transfer_encoding {
                         fail!(
                           UNEXPECTED_CONTENT_LENGTH,
@@ -560,6 +665,17 @@ impl Parser {
                     }
                     // RFC 9112 section 6.1
                     (17, case_insensitive_string!("transfer-encoding")) => {
+                      header = Some(HEADER_TRANSFER_ENCODING);
+
+                      if folded {
+                        fail!(UNEXPECTED_CHARACTER, "Unexpected obs-fold in Transfer-Encoding header");
+                      } else if spaced {
+                        fail!(
+                          UNEXPECTED_CHARACTER,
+                          "Unexpected whitespace before colon in Transfer-Encoding header"
+                        );
+                      }
+
                       if self.has_content_length {
                         fail!(
                           UNEXPECTED_TRANSFER_ENCODING,
@@ -570,6 +686,17 @@ impl Parser {
                           UNEXPECTED_TRANSFER_ENCODING,
                           "Unexpected Transfer-Encoding header for a response without body"
                         );
+                      } else if protocol != PROTOCOL_HTTP {
+                        fail!(
+                          UNEXPECTED_TRANSFER_ENCODING,
+                          "Unexpected Transfer-Encoding header in a RTSP or ICE message"
+                        );
+                      } else if self.version_minor == 0 {
+                        // RFC 9112 section 6.1
+                        fail!(
+                          UNEXPECTED_TRANSFER_ENCODING,
+                          "Unexpected Transfer-Encoding header in a HTTP/1.0 message"
+                        );
                       }
 
                       if !strip_ows_fast(data, &mut header_value_start, &mut header_value_end, false) {
@@ -648,6 +775,17 @@ impl Parser {
                     }
                     // RFC 9112 section 9.6
                     (10, case_insensitive_string!("connection")) => {
+                      header = Some(HEADER_CONNECTION);
+
+                      if folded {
+                        fail!(UNEXPECTED_CHARACTER, "Unexpected obs-fold in Connection header");
+                      } else if spaced {
+                        fail!(
+                          UNEXPECTED_CHARACTER,
+                          "Unexpected whitespace before colon in Connection header"
+                        );
+                      }
+
                       if !strip_ows_fast(data, &mut header_value_start, &mut header_value_end, false) {
                         fail!(UNEXPECTED_CHARACTER, "Expected Connection header value");
                       }
@@ -657,12 +795,16 @@ impl Parser {
                           self.has_connection_close = true;
                         }
                         case_insensitive_string!("keep-alive") => {
-                          // Keep-alive is implicit unless Connection: close is
+                          // Keep-alive is implicit in HTTP/1.1 unless Connection: close is
                           // present.
+                          self.has_connection_keep_alive = true;
                         }
                         case_insensitive_string!("upgrade") => {
                           self.has_connection_upgrade = true;
                         }
+                        case_insensitive_string!("http2-settings") => {
+                          self.has_connection_http2_settings = true;
+                        }
                         _ => {
                           // Comma separated values
                           let mut token_start = header_value_start;
@@ -692,7 +834,12 @@ impl Parser {
                               case_insensitive_string!("upgrade") => {
                                 self.has_connection_upgrade = true;
                               }
-                              case_insensitive_string!("keep-alive") => {}
+                              case_insensitive_string!("keep-alive") => {
+                                self.has_connection_keep_alive = true;
+                              }
+                              case_insensitive_string!("http2-settings") => {
+                                self.has_connection_http2_settings = true;
+                              }
                               _ => {
                                 if !validate_token(data, token_start, token_end) {
                                   fail!(UNEXPECTED_CHARACTER, "Invalid Connection header value");
@@ -710,6 +857,8 @@ impl Parser {
                       }
                     }
                     (7, case_insensitive_string!("trailer")) => {
+                      header = Some(HEADER_TRAILER);
+
                       self.has_trailers = true;
 
                       if !strip_ows_fast(data, &mut header_value_start, &mut header_value_end, false) {
@@ -717,11 +866,23 @@ impl Parser {
                       }
                     }
                     (7, case_insensitive_string!("upgrade")) => {
+                      header = Some(HEADER_UPGRADE);
+
+                      if folded {
+                        fail!(UNEXPECTED_CHARACTER, "Unexpected obs-fold in Upgrade header");
+                      } else if spaced {
+                        fail!(
+                          UNEXPECTED_CHARACTER,
+                          "Unexpected whitespace before colon in Upgrade header"
+                        );
+                      }
+
                       if !strip_ows_fast(data, &mut header_value_start, &mut header_value_end, false) {
                         fail!(UNEXPECTED_CHARACTER, "Expected Upgrade header value");
                       }
 
                       let mut token_start = header_value_start;
+                      let mut protocols = 0;
                       loop {
                         while token_start < header_value_end && is_ws(data[token_start]) {
                           token_start += 1;
@@ -741,6 +902,17 @@ impl Parser {
                           fail!(UNEXPECTED_CHARACTER, "Expected Upgrade header value");
                         }
 
+                        // The cap only bounds the events, so it is not enforced when they are not
+                        // requested
+                        protocols += 1;
+                        if protocols > UPGRADE_MAX_PROTOCOLS
+                          && active_events
+                            & (EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME | EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_VERSION)
+                            != 0
+                        {
+                          fail!(TOO_MANY_UPGRADE_PROTOCOLS, "Too many protocols in Upgrade header");
+                        }
+
                         let protocol_name_end = find_char(data, token_start, token_end, b'/').unwrap_or(token_end);
                         if !validate_token(data, token_start, protocol_name_end) {
                           fail!(UNEXPECTED_CHARACTER, "Invalid Upgrade header value");
@@ -753,6 +925,9 @@ impl Parser {
                           {
                             fail!(UNEXPECTED_CHARACTER, "Invalid Upgrade header value");
                           }
+                        } else if let case_insensitive_string!("h2c") = data[token_start..token_end] {
+                          // RFC 7540 section 3.2
+                          self.has_upgrade_h2c = true;
                         }
 
                         if token_end_raw == header_value_end {
@@ -763,6 +938,50 @@ impl Parser {
                       }
 
                       self.has_upgrade = true;
+                      upgrade_protocols = Some((header_value_start, header_value_end));
+                    }
+                    // RFC 7540 section 3.2.1
+                    (14, case_insensitive_string!("http2-settings")) => {
+                      header = Some(HEADER_HTTP2_SETTINGS);
+
+                      // The header is only meaningful in requests
+                      if self.is_request {
+                        if folded {
+                          fail!(UNEXPECTED_CHARACTER, "Unexpected obs-fold in HTTP2-Settings header");
+                        }
+
+                        if self.has_http2_settings {
+                          fail!(INVALID_HTTP2_SETTINGS, "Invalid duplicate HTTP2-Settings header");
+                        }
+
+                        if !strip_ows_fast(data, &mut header_value_start, &mut header_value_end, false) {
+                          fail!(UNEXPECTED_CHARACTER, "Expected HTTP2-Settings header value");
+                        }
+
+                        // Each setting is 6 bytes long, which are always encoded as 8 characters
+                        let settings_length = header_value_end - header_value_start;
+                        if settings_length % 8 != 0 {
+                          fail!(INVALID_HTTP2_SETTINGS, "Invalid HTTP2-Settings header");
+                        } else if settings_length > HTTP2_MAX_SETTINGS * 8
+                          && active_events & EVENT_ACTIVE_ON_HTTP2_SETTING != 0
+                        {
+                          fail!(INVALID_HTTP2_SETTINGS, "Too many settings in HTTP2-Settings header");
+                        }
+
+                        let mut i = header_value_start;
+                        while i < header_value_end {
+                          if decode_base64url(data[i]).is_none() {
+                            fail!(INVALID_HTTP2_SETTINGS, "Invalid HTTP2-Settings header");
+                          }
+
+                          i += 1;
+                        }
+
+                        self.has_http2_settings = true;
+                        http2_settings = Some((header_value_start, header_value_end));
+                      } else if has_header_value_event {
+                        strip_ows_fast(data, &mut header_value_start, &mut header_value_end, true);
+                      }
                     }
                     _ => {
                       if !validate_token(data, header_name_start, header_name_end) {
@@ -777,7 +996,13 @@ impl Parser {
                 }
 
                 if has_header_name_event {
-                  event_with_range!(on_header_name, header_name_start, header_name_end - header_name_start);
+                  self.known_header = header.unwrap_or_else(|| known_header(&data[header_name_start..header_name_end]));
+                  event_with_range!(
+                    on_header_name,
+                    header_name_start,
+                    header_name_end - header_name_start,
+                    self.known_header
+                  );
                 }
 
                 if has_header_value_event {
@@ -788,7 +1013,54 @@ impl Parser {
                   );
                 }
 
-                advance!(cr + 2);
+                // RFC 9110 section 7.8 - Report each protocol, which have already been
+                // validated
+                if let Some((protocols_start, protocols_end)) = upgrade_protocols
+                  && active_events & (EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME | EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_VERSION)
+                    != 0
+                {
+                  let mut token_start = protocols_start;
+                  loop {
+                    let token_end_raw = match find_char(data, token_start, protocols_end, b',') {
+                      Some(comma) => comma,
+                      None => protocols_end,
+                    };
+                    let mut token_end = token_end_raw;
+
+                    if strip_ows_fast(data, &mut token_start, &mut token_end, false) {
+                      match find_char(data, token_start, token_end, b'/') {
+                        Some(slash) => {
+                          event_with_range!(on_upgrade_protocol_name, token_start, slash - token_start);
+                          event_with_range!(on_upgrade_protocol_version, slash + 1, token_end - slash - 1);
+                        }
+                        None => {
+                          event_with_range!(on_upgrade_protocol_name, token_start, token_end - token_start);
+                        }
+                      }
+                    }
+
+                    if token_end_raw == protocols_end {
+                      break;
+                    } else {
+                      token_start = token_end_raw + 1;
+                    }
+                  }
+                }
+
+                if let Some((settings_start, settings_end)) = http2_settings
+                  && active_events & EVENT_ACTIVE_ON_HTTP2_SETTING != 0
+                {
+                  let mut setting_start = settings_start;
+                  while setting_start < settings_end {
+                    if let Some((identifier, value)) = decode_http2_setting(data, setting_start) {
+                      event_with_http2_setting!(on_http2_setting, setting_start, identifier, value);
+                    }
+
+                    setting_start += 8;
+                  }
+                }
+
+                advance!(next_line);
               }
               HeaderLineScanResult::Invalid(invalid) => {
                 match find_char(data, 0, invalid, b':') {
@@ -823,6 +1095,19 @@ impl Parser {
               );
             }
 
+            // RFC 7540 section 3.2.1
+            if self.is_request && self.has_upgrade_h2c && !self.has_http2_settings {
+              fail!(
+                INVALID_HTTP2_SETTINGS,
+                "Missing HTTP2-Settings header for a h2c upgrade"
+              );
+            } else if self.has_http2_settings && !self.has_connection_http2_settings {
+              fail!(
+                INVALID_HTTP2_SETTINGS,
+                "Missing Connection header set to \"HTTP2-Settings\" when using the HTTP2-Settings header"
+              );
+            }
+
             if self.has_trailers && !self.has_chunked_transfer_encoding {
               fail!(
                 UNEXPECTED_TRAILERS,
@@ -832,6 +1117,27 @@ impl Parser {
               fail!(UNEXPECTED_CONTENT, "Unexpected content for the request (GET or HEAD)");
             }
 
+            // RFC 9110 section 9.3.6 - The final response to a CONNECT request
+            if self.expect_connect_response && !self.is_request && (status >= 200 || status == 101) {
+              self.expect_connect_response = false;
+
+              if status / 100 == 2 {
+                if self.has_content_length {
+                  fail!(
+                    UNEXPECTED_CONTENT_LENGTH,
+                    "Unexpected Content-Length header in a successful response to CONNECT"
+                  );
+                } else if self.has_transfer_encoding {
+                  fail!(
+                    UNEXPECTED_TRANSFER_ENCODING,
+                    "Unexpected Transfer-Encoding header in a successful response to CONNECT"
+                  );
+                }
+
+                self.is_connect = true;
+              }
+            }
+
             // In case of Connection: Upgrade or a CONNECT method
             if self.is_connect {
               // In case of CONNECT method
@@ -918,6 +1224,18 @@ impl Parser {
                 }
               } else if self.has_chunked_transfer_encoding {
                 move_to!(chunk_header);
+              } else if protocol == PROTOCOL_RTSP {
+                // RFC 2326 section 4.3 - Bodies always have a Content-Length
+                self.continue_without_data = true;
+                if !self.complete_message(
+                  0,
+                  active_events,
+                  has_complete_events,
+                  has_finish_event,
+                  &mut event_cursor,
+                ) {
+                  suspend!();
+                }
               } else {
                 move_to!(body_with_no_length);
               }
@@ -997,25 +1315,32 @@ impl Parser {
 
           // RFC 9112 section 7.1
           STATE_CHUNK_HEADER => {
-            match find_cr(data, available) {
+            match find_cr(data, available, lenient_flags) {
               Some(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 let chunk_length_start = 0;
                 // Note, the character is optional since chunk extensions are not required
-                let chunk_length_end = match find_char(data, chunk_length_start, cr, b';') {
+                let chunk_length_delimiter = match find_char(data, chunk_length_start, cr, b';') {
                   Some(index) => index,
                   None => cr,
                 };
 
+                let mut chunk_length_end = chunk_length_delimiter;
+                if lenient_flags & LENIENT_SPACES_AFTER_CHUNK_SIZE != 0 {
+                  while chunk_length_end > chunk_length_start && is_ws(data[chunk_length_end - 1]) {
+                    chunk_length_end -= 1;
+                  }
+                }
+
                 if chunk_length_end == 0 {
                   fail!(UNEXPECTED_CHARACTER, "Invalid chunk length character");
                 } else if chunk_length_end - chunk_length_start > 16 {
@@ -1051,8 +1376,8 @@ impl Parser {
                 );
 
                 // There are extensions
-                if chunk_length_end < cr {
-                  advance!(chunk_length_end + 1);
+                if chunk_length_delimiter < cr {
+                  advance!(chunk_length_delimiter + 1);
                   move_to!(chunk_extensions);
                 } else {
                   self.continue_without_data = true;
@@ -1060,10 +1385,10 @@ impl Parser {
                   if self.chunk_size == 0 {
                     event_with_range!(on_chunk, 3, 0);
                     event_with_range!(on_body, 3, 0);
-                    advance!(cr + 2);
+                    advance!(cr + terminator);
                     move_to!(trailer);
                   } else {
-                    advance!(cr + 2);
+                    advance!(cr + terminator);
                     move_to!(chunk_data);
                   }
                 }
@@ -1079,17 +1404,17 @@ impl Parser {
           }
 
           STATE_CHUNK_EXTENSIONS => {
-            match find_cr(data, available) {
+            match find_cr(data, available, lenient_flags) {
               Some(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 let mut name_start = 0;
                 // Find the first between = or ;
@@ -1111,10 +1436,10 @@ impl Parser {
                   if name_end_raw == cr {
                     if self.chunk_size == 0 {
                       event_with_range!(on_body, 0, 0);
-                      advance!(cr + 2);
+                      advance!(cr + terminator);
                       move_to!(trailer);
                     } else {
-                      advance!(cr + 2);
+                      advance!(cr + terminator);
                       move_to!(chunk_data);
                     }
                   } else {
@@ -1213,10 +1538,10 @@ impl Parser {
                   } else {
                     if self.chunk_size == 0 {
                       event_with_range!(on_body, 0, 0);
-                      advance!(cr + 2);
+                      advance!(cr + terminator);
                       move_to!(trailer);
                     } else {
-                      advance!(cr + 2);
+                      advance!(cr + terminator);
                       move_to!(chunk_data);
                     }
                   }
@@ -1238,13 +1563,22 @@ impl Parser {
 
             // No more data for this chunk, just wait for the CRLF
             if expected == 0 {
-              if available < 2 {
-                suspend!();
-              } else if data[0] != b'\r' || data[1] != b'\n' {
-                fail!(UNEXPECTED_CHARACTER, "Expected CRLF after chunk data");
-              } else {
+              if available >= 2 && data[0] == b'\r' && data[1] == b'\n' {
                 advance!(2);
                 move_to!(chunk_header);
+              } else if (available > 0 && data[0] == b'\n' && lenient_flags & LENIENT_OPTIONAL_CR_BEFORE_LF != 0)
+                || (available >= 2 && data[0] == b'\r' && lenient_flags & LENIENT_OPTIONAL_LF_AFTER_CR != 0)
+              {
+                // Bare LF or bare CR
+                advance!(1);
+                move_to!(chunk_header);
+              } else if available > 0 && data[0] != b'\r' && lenient_flags & LENIENT_OPTIONAL_CRLF_AFTER_CHUNK != 0 {
+                // The next chunk header follows the data directly
+                move_to!(chunk_header);
+              } else if available < 2 {
+                suspend!();
+              } else {
+                fail!(UNEXPECTED_CHARACTER, "Expected CRLF after chunk data");
               }
             } else {
               let mut to_consume = expected.min(available as u64);
@@ -1279,24 +1613,24 @@ impl Parser {
 
           // RFC 9112 section 7.1.2
           STATE_TRAILER => {
-            match find_header_line_end(data.as_ptr(), available) {
+            match find_header_line_end(data, available, lenient_flags) {
               HeaderLineScanResult::Cr(cr) => {
-                match ensure_valid_line(data, cr, available) {
-                  MatchResult::Continue => {}
+                let terminator = match ensure_valid_line(data, cr, available, lenient_flags) {
+                  MatchResult::Continue(terminator) => terminator,
                   MatchResult::Suspend => {
                     suspend!();
                   }
                   MatchResult::Stop => {
                     fail!(UNEXPECTED_CHARACTER, "Expected CRLF");
                   }
-                }
+                };
 
                 // No more trailers or no trailers at all, message completed
                 if cr == 0 {
-                  event_with_range!(on_trailers, 2, 0);
+                  event_with_range!(on_trailers, terminator, 0);
                   self.continue_without_data = true;
                   if !self.complete_message(
-                    2,
+                    terminator,
                     active_events,
                     has_complete_events,
                     has_finish_event,
@@ -1304,7 +1638,7 @@ impl Parser {
                   ) {
                     suspend!();
                   }
-                  advance!(2);
+                  advance!(terminator);
                   next!();
                 }
 
@@ -1342,7 +1676,7 @@ impl Parser {
                     trailer_value_end - trailer_value_start
                   );
                 }
-                advance!(cr + 2);
+                advance!(cr + terminator);
               }
               HeaderLineScanResult::Invalid(invalid) => {
                 match find_char(data, 0, invalid, b':') {
@@ -1366,8 +1700,15 @@ impl Parser {
 
           // Return PAUSE makes this method idempotent without failing - In this state
           // all data is ignored since the connection is not in HTTP anymore
+          // Unless requested, the data following a tunnel is left to the caller.
           STATE_TUNNEL => {
-            suspend!();
+            if !self.emit_tunnel_data || available == 0 {
+              suspend!();
+            }
+
+            event_with_range!(on_tunnel_data, 0, available);
+            self.tunnel_bytes += available as u64;
+            advance!(available);
           }
 
           _ => {
@@ -1382,7 +1723,7 @@ impl Parser {
         data = &data[advanced..];
         available -= advanced;
 
-        #[cfg(any(debug_assertions, feature = "debug"))]
+        #[cfg(all(feature = "std", any(debug_assertions, feature = "debug")))]
         if self.debug {
           eprintln!(
             "[milo_parser::debug] loop before processing: position={}, advanced={}, available={}, \
@@ -1410,7 +1751,11 @@ impl Parser {
       }
 
       // Show the duration of the operation
-      #[cfg(all(not(target_family = "wasm"), any(debug_assertions, feature = "debug")))]
+      #[cfg(all(
+        feature = "std",
+        not(target_family = "wasm"),
+        any(debug_assertions, feature = "debug")
+      ))]
       if self.debug {
         let duration = Instant::now().duration_since(last).as_nanos();
 
@@ -1426,7 +1771,7 @@ impl Parser {
       }
     }
 
-    #[cfg(any(debug_assertions, feature = "debug"))]
+    #[cfg(all(feature = "std", any(debug_assertions, feature = "debug")))]
     if self.debug {
       eprintln!("[milo_parser::debug] loop exit");
     }
@@ -1435,27 +1780,43 @@ impl Parser {
     self.parsed += consumed as u64;
 
     if self.manage_unconsumed {
-      unsafe {
-        // Drop any previous retained data
-        if unconsumed_len > 0 {
-          let _ = from_raw_parts(self.unconsumed, unconsumed_len);
-        }
-
-        // If less bytes were consumed than requested, copy the unconsumed portion in
-        // the self.for the next iteration
-        if consumed < limit {
-          let (ptr, len, _) = data.to_vec().into_raw_parts();
+      let remaining = limit - consumed;
+
+      if remaining == 0 || self.state == STATE_ERROR {
+        self.unconsumed = ptr::null();
+        self.unconsumed_len = 0;
+      } else if self.max_unconsumed_length > 0 && remaining > self.max_unconsumed_length {
+        self.unconsumed = ptr::null();
+        self.unconsumed_len = 0;
+        self.fail_with_events(
+          ERROR_UNCONSUMED_DATA_TOO_LARGE,
+          "Unconsumed data too large",
+          &mut event_cursor,
+        );
+      } else if aggregated {
+        // The unconsumed portion is already in the carry buffer. It is moved to its
+        // beginning on the next iteration, so that ranges of the events emitted
+        // by this iteration remain valid until then.
+        self.unconsumed = unsafe { self.unconsumed_buffer.add(consumed) };
+        self.unconsumed_len = remaining;
+      } else {
+        // Copy the unconsumed portion in the carry buffer for the next iteration
+        self.prepare_unconsumed_buffer(remaining, 0);
 
-          self.unconsumed = ptr;
-          self.unconsumed_len = len;
-        } else {
-          self.unconsumed = ptr::null();
-          self.unconsumed_len = 0;
+        unsafe {
+          ptr::copy_nonoverlapping(data.as_ptr(), self.unconsumed_buffer, remaining);
         }
+
+        self.unconsumed = self.unconsumed_buffer;
+        self.unconsumed_len = remaining;
       }
     }
 
-    #[cfg(all(not(target_family = "wasm"), any(debug_assertions, feature = "debug")))]
+    #[cfg(all(
+      feature = "std",
+      not(target_family = "wasm"),
+      any(debug_assertions, feature = "debug")
+    ))]
     if self.debug {
       let duration = Instant::now().duration_since(start).as_nanos();
 
@@ -1523,6 +1884,43 @@ impl Parser {
     }
   }
 
+  /// Leaves the tunnel state, for instance when the peer refused an upgrade or
+  /// a `CONNECT` request, so that the following data is parsed as a new
+  /// message.
+  ///
+  /// When `manage_unconsumed` is enabled, the data buffered after the tunnel
+  /// was entered is parsed immediately and the number of consumed bytes is
+  /// returned. Otherwise `0` is returned and the caller has to pass the data
+  /// not consumed by the last `parse` call again.
+  ///
+  /// When `emit_tunnel_data` is enabled, the tunnel data is consumed as soon
+  /// as it is received, so the flag must stay disabled until the tunnel is
+  /// confirmed. Cancelling a tunnel which already consumed data fails with
+  /// `UNEXPECTED_STATE`.
+  pub fn cancel_tunnel(&mut self) -> usize {
+    if self.state != STATE_TUNNEL {
+      self.fail(ERROR_UNEXPECTED_STATE, "Invalid state");
+      return 0;
+    } else if self.tunnel_bytes > 0 {
+      self.fail(ERROR_UNEXPECTED_STATE, "Cannot cancel a tunnel which consumed data");
+      return 0;
+    }
+
+    self.state = STATE_START;
+    self.clear();
+
+    unsafe {
+      *self.events = EVENT_END;
+    }
+
+    if !self.manage_unconsumed || self.unconsumed_len == 0 {
+      return 0;
+    }
+
+    // The buffered data is prepended by parse, so no new input is needed
+    self.parse(ptr::NonNull::dangling().as_ptr(), 0)
+  }
+
   // RFC 9110 section 6.4.1 - Message completed.
   #[inline(always)]
   fn complete_message(
@@ -1533,14 +1931,13 @@ impl Parser {
     has_finish_event: bool,
     event_cursor: &mut usize,
   ) -> bool {
-    if has_complete_events {
-      if (active_events & EVENT_ACTIVE_ON_MESSAGE_COMPLETE != 0
+    if has_complete_events
+      && ((active_events & EVENT_ACTIVE_ON_MESSAGE_COMPLETE != 0
         && !self.try_emit_event_range(event_cursor, EVENT_MESSAGE_COMPLETE, self.position + offset, 0))
         || (active_events & EVENT_ACTIVE_ON_RESET != 0
-          && !self.try_emit_event_range(event_cursor, EVENT_RESET, self.position + offset, 0))
-      {
-        return false;
-      }
+          && !self.try_emit_event_range(event_cursor, EVENT_RESET, self.position + offset, 0)))
+    {
+      return false;
     }
 
     self.continue_without_data = false;
@@ -0,0 +1,181 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::io::{self, Read};
+
+use crate::*;
+
+const READER_EVENTS: u64 = EVENT_ACTIVE_ON_DATA
+  | EVENT_ACTIVE_ON_TRAILER_NAME
+  | EVENT_ACTIVE_ON_TRAILER_VALUE
+  | EVENT_ACTIVE_ON_MESSAGE_COMPLETE;
+
+const READ_BUFFER_SIZE: usize = 8192;
+
+/// A blocking reader exposing the decoded body of a message via `Read`.
+///
+/// The reader wraps a source positioned after the head of a message, which
+/// has been parsed with `suspend_after_headers`. The body is decoded using the
+/// framing determined by the head: `Content-Length`, chunked encoding or the
+/// end of the source. Trailers are available once the reader returned EOF.
+///
+/// The parser is never fed data following the body, so a pipelined message is
+/// left untouched and is returned by `into_parts`.
+#[derive(Debug)]
+pub struct BodyReader<R> {
+  inner: R,
+  parser: Parser,
+  buffer: Vec<u8>,
+  decoded: Vec<u8>,
+  decoded_position: usize,
+  trailers: Vec<Header>,
+  complete: bool,
+}
+
+impl<R: Read> BodyReader<R> {
+  /// Creates a new body reader.
+  ///
+  /// The parser must have been suspended after the headers of the message.
+  /// `buffered` contains the data following the head which was already read
+  /// from `inner`.
+  ///
+  /// The events needed by the reader are enabled and `manage_unconsumed` and
+  /// `suspend_after_headers` are disabled on the parser.
+  pub fn new(inner: R, mut parser: Parser, buffered: &[u8]) -> BodyReader<R> {
+    parser.active_events |= READER_EVENTS;
+    parser.manage_unconsumed = false;
+    parser.suspend_after_headers = false;
+
+    BodyReader {
+      inner,
+      parser,
+      buffer: buffered.to_vec(),
+      decoded: Vec::new(),
+      decoded_position: 0,
+      trailers: Vec::new(),
+      complete: false,
+    }
+  }
+
+  /// Returns the underlying parser.
+  pub fn parser(&self) -> &Parser { &self.parser }
+
+  /// Returns the wrapped source.
+  pub fn get_ref(&self) -> &R { &self.inner }
+
+  /// Returns the wrapped source.
+  ///
+  /// Reading directly from it will corrupt the body.
+  pub fn get_mut(&mut self) -> &mut R { &mut self.inner }
+
+  /// Returns `true` if the whole body has been decoded.
+  pub fn is_complete(&self) -> bool { self.complete }
+
+  /// Returns the trailers of the message. They are only available once the
+  /// whole body has been decoded.
+  pub fn trailers(&self) -> &[Header] { &self.trailers }
+
+  /// Consumes the reader, returning the wrapped source, the parser and the
+  /// data which was read from the source but does not belong to the body.
+  pub fn into_parts(self) -> (R, Parser, Vec<u8>) { (self.inner, self.parser, self.buffer) }
+
+  // Returns how much of the buffer can be fed to the parser without exceeding
+  // the current framing unit, so that the following message is never parsed.
+  fn limit(&self) -> usize {
+    let line = || memchr::memchr(b'\n', &self.buffer).map_or(self.buffer.len(), |index| index + 1);
+    let bounded = |remaining: u64| self.buffer.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+
+    match self.parser.state {
+      // The decision consumes no data and a body might not follow
+      STATE_BODY_DECISION => 0,
+      STATE_BODY_VIA_CONTENT_LENGTH => bounded(self.parser.remaining_content_length),
+      STATE_CHUNK_DATA if self.parser.remaining_chunk_size > 0 => bounded(self.parser.remaining_chunk_size),
+      STATE_BODY_WITH_NO_LENGTH => self.buffer.len(),
+      _ => line(),
+    }
+  }
+
+  fn parse(&mut self) -> io::Result<usize> {
+    let limit = self.limit();
+    let consumed = self.parser.parse(self.buffer.as_ptr(), limit);
+
+    for event in self.parser.iter_events(&self.buffer[..limit]) {
+      if let Event::Range { event, data, .. } = event {
+        match event {
+          Events::DATA => self.decoded.extend_from_slice(data),
+          Events::TRAILER_NAME => {
+            self.trailers.push(Header {
+              name: String::from_utf8_lossy(data).into_owned(),
+              value: Vec::new(),
+            })
+          }
+          Events::TRAILER_VALUE => {
+            if let Some(trailer) = self.trailers.last_mut() {
+              trailer.value.extend_from_slice(data);
+            }
+          }
+          Events::MESSAGE_COMPLETE => self.complete = true,
+          _ => (),
+        }
+      }
+    }
+
+    self.buffer.drain(..consumed);
+
+    match self.parser.error() {
+      Some(error) => Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+      None => Ok(consumed),
+    }
+  }
+
+  fn finish(&mut self) -> io::Result<()> {
+    self.parser.finish();
+
+    // Only a body delimited by the end of the connection can be completed
+    self.complete = self.parser.state == STATE_FINISH;
+
+    match self.parser.error() {
+      Some(error) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, error)),
+      None => Ok(()),
+    }
+  }
+}
+
+impl<R: Read> Read for BodyReader<R> {
+  fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+    loop {
+      if self.decoded_position < self.decoded.len() {
+        let available = &self.decoded[self.decoded_position..];
+        let len = available.len().min(output.len());
+
+        output[..len].copy_from_slice(&available[..len]);
+        self.decoded_position += len;
+
+        if self.decoded_position == self.decoded.len() {
+          self.decoded.clear();
+          self.decoded_position = 0;
+        }
+
+        return Ok(len);
+      }
+
+      if self.complete || output.is_empty() {
+        return Ok(0);
+      }
+
+      let consumed = self.parse()?;
+
+      if consumed > 0 || self.parser.continue_without_data || self.complete {
+        continue;
+      }
+
+      let mut chunk = [0u8; READ_BUFFER_SIZE];
+      let read = self.inner.read(&mut chunk)?;
+
+      if read == 0 {
+        self.finish()?;
+      } else {
+        self.buffer.extend_from_slice(&chunk[..read]);
+      }
+    }
+  }
+}
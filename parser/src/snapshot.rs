@@ -0,0 +1,379 @@
+use alloc::vec::Vec;
+use core::{fmt, ptr};
+
+use crate::*;
+
+/// The magic bytes at the beginning of each snapshot.
+pub const SNAPSHOT_MAGIC: &[u8; 4] = b"MILO";
+/// The version of the snapshot format.
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+/// The reason why a snapshot could not be restored.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+  InvalidMagic = 1,
+  UnsupportedVersion,
+  InvalidLength,
+  InvalidValue,
+}
+
+impl SnapshotError {
+  pub fn as_str(&self) -> &str {
+    match self {
+      SnapshotError::InvalidMagic => "The data is not a parser snapshot",
+      SnapshotError::UnsupportedVersion => "The snapshot version is not supported",
+      SnapshotError::InvalidLength => "The snapshot length is not valid",
+      SnapshotError::InvalidValue => "The snapshot contains an invalid value",
+    }
+  }
+}
+
+impl fmt::Display for SnapshotError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.as_str()) }
+}
+
+impl core::error::Error for SnapshotError {}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+  fn u8(&mut self, value: u8) { self.0.push(value); }
+
+  fn bool(&mut self, value: bool) { self.0.push(value as u8); }
+
+  fn u32(&mut self, value: u32) { self.0.extend_from_slice(&value.to_le_bytes()); }
+
+  fn u64(&mut self, value: u64) { self.0.extend_from_slice(&value.to_le_bytes()); }
+
+  fn usize(&mut self, value: usize) { self.u64(value as u64); }
+
+  fn bytes(&mut self, value: &[u8]) {
+    self.usize(value.len());
+    self.0.extend_from_slice(value);
+  }
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+  cursor: usize,
+}
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+    let end = self.cursor.checked_add(len).ok_or(SnapshotError::InvalidLength)?;
+    let value = self.data.get(self.cursor..end).ok_or(SnapshotError::InvalidLength)?;
+
+    self.cursor = end;
+    Ok(value)
+  }
+
+  fn u8(&mut self) -> Result<u8, SnapshotError> { Ok(self.take(1)?[0]) }
+
+  fn bool(&mut self) -> Result<bool, SnapshotError> {
+    match self.u8()? {
+      0 => Ok(false),
+      1 => Ok(true),
+      _ => Err(SnapshotError::InvalidValue),
+    }
+  }
+
+  fn u32(&mut self) -> Result<u32, SnapshotError> { Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap())) }
+
+  fn u64(&mut self) -> Result<u64, SnapshotError> { Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap())) }
+
+  fn usize(&mut self) -> Result<usize, SnapshotError> {
+    usize::try_from(self.u64()?).map_err(|_| SnapshotError::InvalidValue)
+  }
+
+  fn bytes(&mut self) -> Result<&'a [u8], SnapshotError> {
+    let len = self.usize()?;
+    self.take(len)
+  }
+}
+
+impl Parser {
+  /// Serializes the message state, flags, counters, configuration and
+  /// retained unconsumed data of the parser.
+  ///
+  /// The context, the callbacks and the event buffer are not included.
+  pub fn snapshot(&self) -> Vec<u8> {
+    let mut writer = Writer(Vec::with_capacity(256 + self.unconsumed_len));
+
+    writer.0.extend_from_slice(SNAPSHOT_MAGIC);
+    writer.u8(SNAPSHOT_VERSION);
+
+    // User writable
+    writer.usize(self.max_start_line_length);
+    writer.usize(self.max_header_length);
+    writer.u64(self.max_body_payload);
+    writer.usize(self.max_unconsumed_length);
+    writer.u64(self.lenient_flags);
+    writer.u8(self.protocol);
+    writer.bool(self.autodetect);
+    writer.bool(self.is_request);
+    writer.bool(self.suspend_after_headers);
+    writer.bool(self.manage_unconsumed);
+    writer.bool(self.continue_without_data);
+    writer.bool(self.is_connect);
+    writer.bool(self.skip_body);
+    writer.bool(self.expect_connect_response);
+    writer.bool(self.debug);
+    writer.bool(self.allow_http10);
+    writer.bool(self.emit_tunnel_data);
+
+    // Configuration
+    writer.usize(self.config.max_start_line_length);
+    writer.usize(self.config.max_header_length);
+    writer.u64(self.config.max_body_payload);
+    writer.usize(self.config.max_unconsumed_length);
+    writer.u64(self.config.lenient_flags);
+    writer.u8(self.config.message_type);
+    writer.u8(self.config.protocol);
+    writer.bool(self.config.suspend_after_headers);
+    writer.bool(self.config.manage_unconsumed);
+    writer.bool(self.config.skip_body);
+    writer.bool(self.config.debug);
+    writer.bool(self.config.allow_http10);
+    writer.bool(self.config.emit_tunnel_data);
+    writer.bool(self.has_config);
+
+    // Generic state
+    writer.u64(self.parsed);
+    writer.usize(self.position);
+    writer.u64(self.error_offset);
+    writer.u8(self.state);
+    writer.bool(self.paused);
+    writer.u8(self.error_code);
+    writer.u8(self.error_state);
+
+    // Current message flags
+    writer.u64(self.content_length);
+    writer.u64(self.chunk_size);
+    writer.u64(self.remaining_content_length);
+    writer.u64(self.remaining_chunk_size);
+    writer.u64(self.tunnel_bytes);
+    writer.u32(self.status);
+    writer.u8(self.method);
+    writer.u8(self.version_minor);
+    writer.bool(self.has_content_length);
+    writer.bool(self.has_transfer_encoding);
+    writer.bool(self.has_chunked_transfer_encoding);
+    writer.bool(self.has_connection_close);
+    writer.bool(self.has_connection_keep_alive);
+    writer.bool(self.has_connection_upgrade);
+    writer.bool(self.has_upgrade);
+    writer.bool(self.has_upgrade_h2c);
+    writer.bool(self.has_http2_settings);
+    writer.bool(self.has_connection_http2_settings);
+    writer.bool(self.has_trailers);
+
+    // Callback handling
+    writer.u64(self.active_callbacks);
+    writer.u64(self.active_events);
+
+    // Complex data types
+    writer.bytes(&self.error_description[..self.error_description_len as usize]);
+
+    if self.unconsumed_len > 0 {
+      writer.bytes(unsafe { slice::from_raw_parts(self.unconsumed, self.unconsumed_len) });
+    } else {
+      writer.bytes(&[]);
+    }
+
+    writer.0
+  }
+
+  /// Restores a snapshot created by `snapshot`.
+  ///
+  /// The snapshot is fully validated before being applied, so the parser is
+  /// left untouched if an error is returned. The context, the callbacks and
+  /// the event buffer are not modified.
+  pub fn restore(&mut self, data: &[u8]) -> Result<(), SnapshotError> {
+    let mut reader = Reader { data, cursor: 0 };
+
+    if reader
+      .take(SNAPSHOT_MAGIC.len())
+      .map_err(|_| SnapshotError::InvalidMagic)?
+      != SNAPSHOT_MAGIC
+    {
+      return Err(SnapshotError::InvalidMagic);
+    }
+
+    if reader.u8()? != SNAPSHOT_VERSION {
+      return Err(SnapshotError::UnsupportedVersion);
+    }
+
+    // User writable
+    let max_start_line_length = reader.usize()?;
+    let max_header_length = reader.usize()?;
+    let max_body_payload = reader.u64()?;
+    let max_unconsumed_length = reader.usize()?;
+    let lenient_flags = reader.u64()?;
+    let protocol = reader.u8()?;
+    let autodetect = reader.bool()?;
+    let is_request = reader.bool()?;
+    let suspend_after_headers = reader.bool()?;
+    let manage_unconsumed = reader.bool()?;
+    let continue_without_data = reader.bool()?;
+    let is_connect = reader.bool()?;
+    let skip_body = reader.bool()?;
+    let expect_connect_response = reader.bool()?;
+    let debug = reader.bool()?;
+    let allow_http10 = reader.bool()?;
+    let emit_tunnel_data = reader.bool()?;
+
+    // Configuration
+    let config = ParserConfig {
+      max_start_line_length: reader.usize()?,
+      max_header_length: reader.usize()?,
+      max_body_payload: reader.u64()?,
+      max_unconsumed_length: reader.usize()?,
+      lenient_flags: reader.u64()?,
+      message_type: reader.u8()?,
+      protocol: reader.u8()?,
+      suspend_after_headers: reader.bool()?,
+      manage_unconsumed: reader.bool()?,
+      skip_body: reader.bool()?,
+      debug: reader.bool()?,
+      allow_http10: reader.bool()?,
+      emit_tunnel_data: reader.bool()?,
+    };
+    let has_config = reader.bool()?;
+
+    if has_config && config.validate().is_err() {
+      return Err(SnapshotError::InvalidValue);
+    }
+
+    // Generic state
+    let parsed = reader.u64()?;
+    let position = reader.usize()?;
+    let error_offset = reader.u64()?;
+    let state = reader.u8()?;
+    let paused = reader.bool()?;
+    let error_code = reader.u8()?;
+    let error_state = reader.u8()?;
+
+    // Any error code is accepted, as custom ones can be set via fail
+    if States::try_from(state).is_err() || States::try_from(error_state).is_err() {
+      return Err(SnapshotError::InvalidValue);
+    }
+
+    // Current message flags
+    let content_length = reader.u64()?;
+    let chunk_size = reader.u64()?;
+    let remaining_content_length = reader.u64()?;
+    let remaining_chunk_size = reader.u64()?;
+    let tunnel_bytes = reader.u64()?;
+    let status = reader.u32()?;
+    let method = reader.u8()?;
+    let version_minor = reader.u8()?;
+    let has_content_length = reader.bool()?;
+    let has_transfer_encoding = reader.bool()?;
+    let has_chunked_transfer_encoding = reader.bool()?;
+    let has_connection_close = reader.bool()?;
+    let has_connection_keep_alive = reader.bool()?;
+    let has_connection_upgrade = reader.bool()?;
+    let has_upgrade = reader.bool()?;
+    let has_upgrade_h2c = reader.bool()?;
+    let has_http2_settings = reader.bool()?;
+    let has_connection_http2_settings = reader.bool()?;
+    let has_trailers = reader.bool()?;
+
+    if Methods::try_from(method).is_err() || version_minor > 1 {
+      return Err(SnapshotError::InvalidValue);
+    }
+
+    // Callback handling
+    let active_callbacks = reader.u64()?;
+    let active_events = reader.u64()?;
+
+    // Complex data types
+    let error_description = reader.bytes()?;
+    let unconsumed = reader.bytes()?;
+
+    if error_description.len() > 254 || str::from_utf8(error_description).is_err() {
+      return Err(SnapshotError::InvalidValue);
+    }
+
+    if lenient_flags & !LENIENT_ALL != 0 || protocol > PROTOCOL_ICE {
+      return Err(SnapshotError::InvalidValue);
+    }
+
+    if reader.cursor != data.len() {
+      return Err(SnapshotError::InvalidLength);
+    }
+
+    self.max_start_line_length = max_start_line_length;
+    self.max_header_length = max_header_length;
+    self.max_body_payload = max_body_payload;
+    self.max_unconsumed_length = max_unconsumed_length;
+    self.lenient_flags = lenient_flags;
+    self.protocol = protocol;
+    self.autodetect = autodetect;
+    self.is_request = is_request;
+    self.suspend_after_headers = suspend_after_headers;
+    self.manage_unconsumed = manage_unconsumed;
+    self.continue_without_data = continue_without_data;
+    self.is_connect = is_connect;
+    self.skip_body = skip_body;
+    self.expect_connect_response = expect_connect_response;
+    self.debug = debug;
+    self.allow_http10 = allow_http10;
+    self.emit_tunnel_data = emit_tunnel_data;
+    self.config = config;
+    self.has_config = has_config;
+    self.parsed = parsed;
+    self.position = position;
+    self.error_offset = error_offset;
+    self.state = state;
+    self.paused = paused;
+    self.error_code = error_code;
+    self.error_state = error_state;
+    self.content_length = content_length;
+    self.chunk_size = chunk_size;
+    self.remaining_content_length = remaining_content_length;
+    self.remaining_chunk_size = remaining_chunk_size;
+    self.tunnel_bytes = tunnel_bytes;
+    self.status = status;
+    self.method = method;
+    self.version_minor = version_minor;
+    self.has_content_length = has_content_length;
+    self.has_transfer_encoding = has_transfer_encoding;
+    self.has_chunked_transfer_encoding = has_chunked_transfer_encoding;
+    self.has_connection_close = has_connection_close;
+    self.has_connection_keep_alive = has_connection_keep_alive;
+    self.has_connection_upgrade = has_connection_upgrade;
+    self.has_upgrade = has_upgrade;
+    self.has_upgrade_h2c = has_upgrade_h2c;
+    self.has_http2_settings = has_http2_settings;
+    self.has_connection_http2_settings = has_connection_http2_settings;
+    self.has_trailers = has_trailers;
+    self.active_callbacks = active_callbacks;
+    self.active_events = active_events;
+
+    self.error_description[..error_description.len()].copy_from_slice(error_description);
+    self.error_description[error_description.len()] = 0;
+    self.error_description_len = error_description.len() as u8;
+
+    if unconsumed.is_empty() {
+      self.unconsumed = ptr::null();
+      self.unconsumed_len = 0;
+    } else {
+      self.unconsumed_len = 0;
+      self.prepare_unconsumed_buffer(unconsumed.len(), 0);
+
+      unsafe {
+        ptr::copy_nonoverlapping(unconsumed.as_ptr(), self.unconsumed_buffer, unconsumed.len());
+      }
+
+      self.unconsumed_len = unconsumed.len();
+    }
+
+    unsafe {
+      *self.events = EVENT_END;
+    }
+
+    Ok(())
+  }
+}
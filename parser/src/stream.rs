@@ -0,0 +1,212 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::str;
+
+use crate::head::HeaderRef;
+use crate::*;
+
+const STREAM_EVENTS: u64 = EVENT_ACTIVE_ON_REQUEST
+  | EVENT_ACTIVE_ON_RESPONSE
+  | EVENT_ACTIVE_ON_HEADERS
+  | EVENT_ACTIVE_ON_DATA
+  | EVENT_ACTIVE_ON_TRAILER_NAME
+  | EVENT_ACTIVE_ON_TRAILER_VALUE
+  | EVENT_ACTIVE_ON_MESSAGE_COMPLETE
+  | EVENT_ACTIVE_ON_CONNECT
+  | EVENT_ACTIVE_ON_UPGRADE
+  | EVENT_ACTIVE_ON_ERROR;
+
+/// A message of a `MessageStream`, borrowed from its buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamedMessage<'a> {
+  pub is_request: bool,
+  /// The range of the buffer occupied by the message, excluding the empty
+  /// lines preceding it.
+  pub range: Range<usize>,
+  /// The head of the message, including the terminating empty line.
+  pub head: &'a [u8],
+  /// The metadata of the message, as reported by the `on_headers` event.
+  pub metadata: HeadersMetadata,
+  /// The body spans, one per `on_data` event.
+  pub body: Vec<&'a [u8]>,
+  pub trailers: Vec<HeaderRef<'a>>,
+}
+
+/// An iterator over the pipelined messages of a buffer.
+///
+/// The iteration stops after a message with `Connection: close`, after a
+/// message entering tunnel mode or at the end of the buffer, which also
+/// completes a body delimited by the end of the connection. The data
+/// following the last message is available via `remaining`.
+#[derive(Debug)]
+pub struct MessageStream<'a> {
+  input: &'a [u8],
+  parser: Parser,
+  offset: usize,
+  end: usize,
+  done: bool,
+  error: Option<ParseError>,
+  pending: Option<StreamedMessage<'a>>,
+  ready: VecDeque<StreamedMessage<'a>>,
+}
+
+impl<'a> MessageStream<'a> {
+  /// Creates a new stream over a buffer, using a default parser.
+  pub fn new(input: &'a [u8]) -> MessageStream<'a> { Self::with_parser(input, Parser::new()) }
+
+  /// Creates a new stream over a buffer, using an existing parser.
+  ///
+  /// The events needed by the stream are enabled on the parser.
+  /// `manage_unconsumed` is disabled, as the stream always borrows unparsed
+  /// data from the input and exposes it via `remaining`.
+  pub fn with_parser(input: &'a [u8], mut parser: Parser) -> MessageStream<'a> {
+    parser.active_events |= STREAM_EVENTS;
+    parser.manage_unconsumed = false;
+
+    MessageStream {
+      input,
+      parser,
+      offset: 0,
+      end: 0,
+      done: false,
+      error: None,
+      pending: None,
+      ready: VecDeque::new(),
+    }
+  }
+
+  /// Returns the underlying parser.
+  pub fn parser(&self) -> &Parser { &self.parser }
+
+  /// Returns the data following the last completed message.
+  pub fn remaining(&self) -> &'a [u8] { &self.input[self.end..] }
+
+  fn step(&mut self) {
+    let input = &self.input[self.offset..];
+    let consumed = self.parser.parse(input.as_ptr(), input.len());
+    self.process();
+    self.offset += consumed;
+
+    if let Some(error) = self.parser.error() {
+      self.done = true;
+
+      // Data after a message with Connection: close is left as remaining
+      if error.state != States::FINISH {
+        self.error = Some(error);
+      }
+
+      return;
+    }
+
+    match self.parser.state {
+      STATE_FINISH | STATE_TUNNEL => self.done = true,
+      state if consumed == 0 && !self.parser.continue_without_data => {
+        self.done = true;
+
+        if self.offset < self.input.len() && matches!(state, STATE_REQUEST_LINE | STATE_STATUS_LINE) {
+          self.parser.fail(ERROR_UNEXPECTED_EOF, "Unexpected end of data");
+        } else {
+          self.parser.finish();
+          self.process();
+        }
+
+        self.error = self.parser.error();
+      }
+      _ => (),
+    }
+  }
+
+  fn process(&mut self) {
+    let input = self.input;
+    let base = self.offset;
+
+    for event in self.parser.iter_events(&input[base..]) {
+      match event {
+        Event::Range { event, at, data } => {
+          let start = base + at;
+          let span = &input[start..start + data.len()];
+
+          match event {
+            Events::REQUEST | Events::RESPONSE => {
+              self.pending = Some(StreamedMessage {
+                is_request: event == Events::REQUEST,
+                range: start..start,
+                head: &[],
+                metadata: HeadersMetadata {
+                  at: 0,
+                  status_or_method: 0,
+                  should_keep_alive: false,
+                  should_upgrade: false,
+                  has_trailers: false,
+                  body_kind: BodyKind::None,
+                  content_length: 0,
+                  version_minor: 1,
+                },
+                body: Vec::new(),
+                trailers: Vec::new(),
+              });
+            }
+            Events::DATA => {
+              if let Some(message) = &mut self.pending {
+                message.body.push(span);
+              }
+            }
+            Events::TRAILER_NAME => {
+              if let Some(message) = &mut self.pending {
+                message.trailers.push(HeaderRef {
+                  name: str::from_utf8(span).unwrap_or_default(),
+                  value: &[],
+                });
+              }
+            }
+            Events::TRAILER_VALUE => {
+              if let Some(trailer) = self.pending.as_mut().and_then(|message| message.trailers.last_mut()) {
+                trailer.value = span;
+              }
+            }
+            // Messages entering tunnel mode have no on_message_complete event
+            Events::MESSAGE_COMPLETE | Events::CONNECT | Events::UPGRADE => {
+              if let Some(mut message) = self.pending.take() {
+                message.range.end = start;
+                self.end = start;
+                self.ready.push_back(message);
+              }
+            }
+            _ => (),
+          }
+        }
+        Event::Headers(metadata) => {
+          if let Some(message) = &mut self.pending {
+            let end = base + metadata.at;
+            message.head = &input[message.range.start..end];
+            message.metadata = HeadersMetadata { at: end, ..metadata };
+          }
+        }
+        Event::HeaderName { .. } | Event::Http2Setting { .. } | Event::StateChange { .. } | Event::Error { .. } => (),
+      }
+    }
+  }
+}
+
+impl<'a> Iterator for MessageStream<'a> {
+  type Item = Result<StreamedMessage<'a>, ParseError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(message) = self.ready.pop_front() {
+        return Some(Ok(message));
+      }
+
+      if let Some(error) = self.error.take() {
+        return Some(Err(error));
+      }
+
+      if self.done {
+        return None;
+      }
+
+      self.step();
+    }
+  }
+}
@@ -1,7 +1,8 @@
 use core::ffi::{c_uchar, c_void};
+use core::ptr;
 use std::slice;
 
-use crate::Parser;
+use crate::{PROTOCOL_ICE, Parser, ParserConfig, SnapshotError};
 
 #[cfg(any(debug_assertions, feature = "debug"))]
 pub fn debug(message: String) { unsafe { crate::logger(((message.as_ptr() as u64) << 32) + message.len() as u64) } }
@@ -41,6 +42,62 @@ pub fn create() -> *mut c_void {
   ptr
 }
 
+/// Creates a new parser using a configuration. It returns a null pointer if
+/// the configuration is not valid.
+#[unsafe(no_mangle)]
+pub fn create_with_config(config: *const c_void) -> *mut c_void {
+  let Ok(parser) = Parser::with_config(unsafe { *(config as *const ParserConfig) }) else {
+    return ptr::null_mut();
+  };
+
+  let ptr = Box::into_raw(Box::new(parser)) as *mut c_void;
+
+  // Recreate the parser from the box to assign the reference to itself
+  let mut parser = unsafe { Box::from_raw(ptr as *mut Parser) };
+  parser.ptr = ptr;
+  let _ = Box::into_raw(parser);
+
+  ptr
+}
+
+/// Applies a configuration to a parser. It returns `0` if the configuration
+/// is valid, otherwise the `ConfigError` value.
+#[unsafe(no_mangle)]
+pub fn apply_config(parser: *mut c_void, config: *const c_void) -> u8 {
+  unsafe {
+    (*(parser as *mut Parser))
+      .apply_config(*(config as *const ParserConfig))
+      .map_or_else(|error| error as u8, |_| 0)
+  }
+}
+
+/// Creates a new parser configuration with the default values.
+#[unsafe(no_mangle)]
+pub fn create_config() -> *mut c_void { Box::into_raw(Box::new(ParserConfig::default())) as *mut c_void }
+
+/// Destroys a parser configuration.
+#[unsafe(no_mangle)]
+pub fn destroy_config(config: *mut c_void) {
+  if config.is_null() {
+    return;
+  }
+
+  unsafe {
+    let _ = Box::from_raw(config as *mut ParserConfig);
+  }
+}
+
+/// Validates a parser configuration. It returns `0` if the configuration is
+/// valid, otherwise the `ConfigError` value.
+#[unsafe(no_mangle)]
+pub fn validate_config(config: *const c_void) -> u8 {
+  unsafe {
+    (*(config as *const ParserConfig))
+      .validate()
+      .map_or_else(|error| error as u8, |_| 0)
+  }
+}
+
 /// Destroys a parser.
 #[unsafe(no_mangle)]
 pub fn destroy(parser: *mut c_void) {
@@ -53,6 +110,34 @@ pub fn destroy(parser: *mut c_void) {
   }
 }
 
+/// Serializes the state of a parser. The pointer and the length of the
+/// snapshot are packed in the returned value. The snapshot must be freed using
+/// `dealloc`.
+#[unsafe(no_mangle)]
+pub fn snapshot(parser: *const c_void) -> u64 {
+  let snapshot = unsafe { (*(parser as *const Parser)).snapshot().into_boxed_slice() };
+  let len = snapshot.len() as u64;
+  let ptr = Box::into_raw(snapshot) as *mut u8 as u64;
+
+  (ptr << 32) + len
+}
+
+/// Restores the state of a parser from a snapshot. It returns `0` if the
+/// snapshot was restored, otherwise the `SnapshotError` value.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn restore(parser: *mut c_void, data: *const c_uchar, len: usize) -> u8 {
+  if data.is_null() {
+    return SnapshotError::InvalidMagic as u8;
+  }
+
+  unsafe {
+    (*(parser as *mut Parser))
+      .restore(slice::from_raw_parts(data, len))
+      .map_or_else(|error| error as u8, |_| 0)
+  }
+}
+
 /// Resets a parser. The second parameters specifies if to also reset the
 /// parsed counter.
 #[unsafe(no_mangle)]
@@ -82,6 +167,14 @@ pub fn resume(parser: *mut c_void) { unsafe { (*(parser as *mut Parser)).resume(
 #[unsafe(no_mangle)]
 pub fn complete(parser: *mut c_void) { unsafe { (*(parser as *mut Parser)).complete() } }
 
+/// Leaves the tunnel state, parsing the buffered data as a new message.
+///
+/// It returns the number of consumed characters. Without `manage_unconsumed`
+/// it returns `0` and the data not consumed by the last `parse` call must be
+/// passed to it again.
+#[unsafe(no_mangle)]
+pub fn cancel_tunnel(parser: *mut c_void) -> usize { unsafe { (*(parser as *mut Parser)).cancel_tunnel() } }
+
 /// Marks the parser as finished. Any new data received via `parse` will
 /// put the parser in the error state.
 #[unsafe(no_mangle)]
@@ -165,6 +258,26 @@ pub fn get_max_header_length(parser: *const c_void) -> usize {
 #[unsafe(no_mangle)]
 pub fn get_max_body_payload(parser: *const c_void) -> u64 { unsafe { (*(parser as *const Parser)).max_body_payload } }
 
+// Get the parser max_unconsumed_length property.
+#[unsafe(no_mangle)]
+pub fn get_max_unconsumed_length(parser: *const c_void) -> usize {
+  unsafe { (*(parser as *const Parser)).max_unconsumed_length }
+}
+
+// Get the parser lenient_flags property.
+#[unsafe(no_mangle)]
+pub fn get_lenient_flags(parser: *const c_void) -> u64 { unsafe { (*(parser as *const Parser)).lenient_flags } }
+
+// Get the parser protocol property.
+#[unsafe(no_mangle)]
+pub fn get_protocol(parser: *const c_void) -> u8 { unsafe { (*(parser as *const Parser)).protocol } }
+
+// Get the parser unconsumed_allocations property.
+#[unsafe(no_mangle)]
+pub fn get_unconsumed_allocations(parser: *const c_void) -> u64 {
+  unsafe { (*(parser as *const Parser)).unconsumed_allocations }
+}
+
 // Get the parser continue_without_data property.
 #[unsafe(no_mangle)]
 pub fn should_continue_without_data(parser: *const c_void) -> bool {
@@ -179,6 +292,22 @@ pub fn is_connect(parser: *const c_void) -> bool { unsafe { (*(parser as *const
 #[unsafe(no_mangle)]
 pub fn should_skip_body(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).skip_body } }
 
+// Get the parser expect_connect_response property.
+#[unsafe(no_mangle)]
+pub fn should_expect_connect_response(parser: *const c_void) -> bool {
+  unsafe { (*(parser as *const Parser)).expect_connect_response }
+}
+
+// Get the parser allow_http10 property.
+#[unsafe(no_mangle)]
+pub fn should_allow_http10(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).allow_http10 } }
+
+// Get the parser emit_tunnel_data property.
+#[unsafe(no_mangle)]
+pub fn should_emit_tunnel_data(parser: *const c_void) -> bool {
+  unsafe { (*(parser as *const Parser)).emit_tunnel_data }
+}
+
 // Get the parser state property.
 #[unsafe(no_mangle)]
 pub fn get_state(parser: *const c_void) -> u8 { unsafe { (*(parser as *const Parser)).state } }
@@ -199,6 +328,26 @@ pub fn get_error_code(parser: *const c_void) -> u8 { unsafe { (*(parser as *cons
 #[unsafe(no_mangle)]
 pub fn get_method(parser: *const c_void) -> u8 { unsafe { (*(parser as *const Parser)).method } }
 
+// Get the parser known_header property.
+#[unsafe(no_mangle)]
+pub fn get_known_header(parser: *const c_void) -> u8 { unsafe { (*(parser as *const Parser)).known_header } }
+
+// Get the parser http2_setting_identifier property.
+#[unsafe(no_mangle)]
+pub fn get_http2_setting_identifier(parser: *const c_void) -> u16 {
+  unsafe { (*(parser as *const Parser)).http2_setting_identifier }
+}
+
+// Get the parser http2_setting_value property.
+#[unsafe(no_mangle)]
+pub fn get_http2_setting_value(parser: *const c_void) -> u32 {
+  unsafe { (*(parser as *const Parser)).http2_setting_value }
+}
+
+// Get the parser version_minor property.
+#[unsafe(no_mangle)]
+pub fn get_version_minor(parser: *const c_void) -> u8 { unsafe { (*(parser as *const Parser)).version_minor } }
+
 // Get the parser status property.
 #[unsafe(no_mangle)]
 pub fn get_status(parser: *const c_void) -> u32 { unsafe { (*(parser as *const Parser)).status } }
@@ -209,6 +358,12 @@ pub fn has_connection_close(parser: *const c_void) -> bool {
   unsafe { (*(parser as *const Parser)).has_connection_close }
 }
 
+// Get the parser has_connection_keep_alive property.
+#[unsafe(no_mangle)]
+pub fn has_connection_keep_alive(parser: *const c_void) -> bool {
+  unsafe { (*(parser as *const Parser)).has_connection_keep_alive }
+}
+
 // Get the parser has_connection_upgrade property.
 #[unsafe(no_mangle)]
 pub fn has_connection_upgrade(parser: *const c_void) -> bool {
@@ -235,6 +390,10 @@ pub fn get_remaining_chunk_size(parser: *const c_void) -> u64 {
   unsafe { (*(parser as *const Parser)).remaining_chunk_size }
 }
 
+// Get the parser tunnel_bytes property.
+#[unsafe(no_mangle)]
+pub fn get_tunnel_bytes(parser: *const c_void) -> u64 { unsafe { (*(parser as *const Parser)).tunnel_bytes } }
+
 // Get the parser has_content_length property.
 #[unsafe(no_mangle)]
 pub fn has_content_length(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).has_content_length } }
@@ -255,6 +414,20 @@ pub fn has_chunked_transfer_encoding(parser: *const c_void) -> bool {
 #[unsafe(no_mangle)]
 pub fn has_upgrade(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).has_upgrade } }
 
+// Get the parser has_upgrade_h2c property.
+#[unsafe(no_mangle)]
+pub fn has_upgrade_h2c(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).has_upgrade_h2c } }
+
+// Get the parser has_http2_settings property.
+#[unsafe(no_mangle)]
+pub fn has_http2_settings(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).has_http2_settings } }
+
+// Get the parser has_connection_http2_settings property.
+#[unsafe(no_mangle)]
+pub fn has_connection_http2_settings(parser: *const c_void) -> bool {
+  unsafe { (*(parser as *const Parser)).has_connection_http2_settings }
+}
+
 // Get the parser has_trailers property.
 #[unsafe(no_mangle)]
 pub fn has_trailers(parser: *const c_void) -> bool { unsafe { (*(parser as *const Parser)).has_trailers } }
@@ -320,6 +493,35 @@ pub fn set_max_body_payload(parser: *mut c_void, value: u64) {
   }
 }
 
+#[unsafe(no_mangle)]
+pub fn set_max_unconsumed_length(parser: *mut c_void, value: usize) {
+  unsafe {
+    (*(parser as *mut Parser)).max_unconsumed_length = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_lenient_flags(parser: *mut c_void, value: u64) {
+  unsafe {
+    (*(parser as *mut Parser)).lenient_flags = value;
+  }
+}
+
+/// Sets the accepted protocol. It returns `false` and leaves the protocol
+/// unchanged if the value is not one of the `PROTOCOL_*` values.
+#[unsafe(no_mangle)]
+pub fn set_protocol(parser: *mut c_void, value: u8) -> bool {
+  if value > PROTOCOL_ICE {
+    return false;
+  }
+
+  unsafe {
+    (*(parser as *mut Parser)).protocol = value;
+  }
+
+  true
+}
+
 #[unsafe(no_mangle)]
 pub fn set_should_continue_without_data(parser: *mut c_void, value: bool) {
   unsafe {
@@ -334,6 +536,13 @@ pub fn set_should_skip_body(parser: *mut c_void, value: bool) {
   }
 }
 
+#[unsafe(no_mangle)]
+pub fn set_should_expect_connect_response(parser: *mut c_void, value: bool) {
+  unsafe {
+    (*(parser as *mut Parser)).expect_connect_response = value;
+  }
+}
+
 #[unsafe(no_mangle)]
 pub fn set_is_connect(parser: *mut c_void, value: bool) {
   unsafe {
@@ -348,6 +557,20 @@ pub fn set_debug(parser: *mut c_void, value: bool) {
   }
 }
 
+#[unsafe(no_mangle)]
+pub fn set_should_allow_http10(parser: *mut c_void, value: bool) {
+  unsafe {
+    (*(parser as *mut Parser)).allow_http10 = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_should_emit_tunnel_data(parser: *mut c_void, value: bool) {
+  unsafe {
+    (*(parser as *mut Parser)).emit_tunnel_data = value;
+  }
+}
+
 #[unsafe(no_mangle)]
 pub fn set_active_callbacks(parser: *mut c_void, value: u64) {
   unsafe {
@@ -361,3 +584,94 @@ pub fn set_active_events(parser: *mut c_void, value: u64) {
     (*(parser as *mut Parser)).active_events = value;
   }
 }
+
+#[unsafe(no_mangle)]
+pub fn set_config_max_start_line_length(config: *mut c_void, value: usize) {
+  unsafe {
+    (*(config as *mut ParserConfig)).max_start_line_length = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_max_header_length(config: *mut c_void, value: usize) {
+  unsafe {
+    (*(config as *mut ParserConfig)).max_header_length = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_max_body_payload(config: *mut c_void, value: u64) {
+  unsafe {
+    (*(config as *mut ParserConfig)).max_body_payload = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_max_unconsumed_length(config: *mut c_void, value: usize) {
+  unsafe {
+    (*(config as *mut ParserConfig)).max_unconsumed_length = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_lenient_flags(config: *mut c_void, value: u64) {
+  unsafe {
+    (*(config as *mut ParserConfig)).lenient_flags = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_message_type(config: *mut c_void, value: u8) {
+  unsafe {
+    (*(config as *mut ParserConfig)).message_type = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_protocol(config: *mut c_void, value: u8) {
+  unsafe {
+    (*(config as *mut ParserConfig)).protocol = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_suspend_after_headers(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).suspend_after_headers = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_manage_unconsumed(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).manage_unconsumed = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_skip_body(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).skip_body = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_debug(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).debug = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_allow_http10(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).allow_http10 = value;
+  }
+}
+
+#[unsafe(no_mangle)]
+pub fn set_config_emit_tunnel_data(config: *mut c_void, value: bool) {
+  unsafe {
+    (*(config as *mut ParserConfig)).emit_tunnel_data = value;
+  }
+}
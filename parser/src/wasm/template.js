@@ -26,10 +26,62 @@ function destroy (parser) {
   this.destroy(parser)
 }
 
+function createWithConfig (config) {
+  const parser = this.create_with_config(config) >>> 0
+
+  if (parser === 0) {
+    throw new Error(`Invalid parser configuration (error ${this.validate_config(config)}).`)
+  }
+
+  return parser
+}
+
+function applyConfig (parser, config) {
+  return this.apply_config(parser, config)
+}
+
+function createConfig () {
+  return this.create_config() >>> 0
+}
+
+function destroyConfig (config) {
+  this.destroy_config(config)
+}
+
+function validateConfig (config) {
+  return this.validate_config(config)
+}
+
 function parse (parser, data, limit) {
   return this.parse(parser, data, limit) >>> 0
 }
 
+function cancelTunnel (parser) {
+  return this.cancel_tunnel(parser) >>> 0
+}
+
+function snapshot (parser) {
+  const raw = this.snapshot(parser)
+  const len = Number(BigInt.asUintN(32, raw))
+  const ptr = Number(raw >> 32n)
+
+  const data = new Uint8Array(this.memory.buffer, ptr, len).slice()
+  this.dealloc(ptr, len)
+
+  return data
+}
+
+function restore (parser, data) {
+  const len = data.length
+  const ptr = this.alloc(len)
+  new Uint8Array(this.memory.buffer, ptr, len).set(data)
+
+  const result = this.restore(parser, ptr, len)
+  this.dealloc(ptr, len)
+
+  return result
+}
+
 function fail (parser, code, description) {
   const len = description.length
   const ptr = this.alloc(len)
@@ -101,8 +153,16 @@ export function setup (env = {}) {
     dealloc: dealloc.bind(wasm),
     create: create.bind(wasm),
     destroy: destroy.bind(wasm),
+    createWithConfig: createWithConfig.bind(wasm),
+    applyConfig: applyConfig.bind(wasm),
+    createConfig: createConfig.bind(wasm),
+    destroyConfig: destroyConfig.bind(wasm),
+    validateConfig: validateConfig.bind(wasm),
     parse: parse.bind(wasm),
+    cancelTunnel: cancelTunnel.bind(wasm),
     fail: fail.bind(wasm),
+    snapshot: snapshot.bind(wasm),
+    restore: restore.bind(wasm),
     hasDebug: hasDebug.bind(wasm),
     clear: wasm.clear,
     complete: wasm.complete,
@@ -0,0 +1,227 @@
+mod helpers;
+
+use milo_parser::{
+  Assembled, ERROR_UNEXPECTED_CHARACTER, Errors, Header, Message, MessageAssembler, Methods, Parser, Request, Response,
+  STATE_TUNNEL,
+};
+
+use crate::helpers::http;
+
+fn header(name: &str, value: &str) -> Header {
+  Header {
+    name: name.into(),
+    value: value.into(),
+  }
+}
+
+fn drain(assembler: &mut MessageAssembler) -> Vec<Assembled> {
+  let mut items = Vec::new();
+
+  while let Some(item) = assembler.next_item() {
+    items.push(item);
+  }
+
+  items
+}
+
+#[test]
+fn assembler_pipelined_requests() {
+  let mut assembler = MessageAssembler::new();
+
+  let input = http(
+    r#"
+      POST /first HTTP/1.1\r\n
+      Content-Length: 3\r\n
+      \r\n
+      abc
+      GET /second?query HTTP/1.1\r\n
+      Host: localhost\r\n
+      Connection: close\r\n
+      \r\n
+    "#,
+  );
+
+  // Feed the input in small pieces to exercise the retained data
+  for chunk in input.as_bytes().chunks(7) {
+    assembler.feed(chunk).unwrap();
+  }
+
+  assert_eq!(
+    drain(&mut assembler),
+    [
+      Assembled::Message(Message::Request(Request {
+        method: Methods::POST,
+        method_name: "POST".into(),
+        url: "/first".into(),
+        version: "1.1".into(),
+        headers: vec![header("Content-Length", "3")],
+        body: b"abc".to_vec(),
+        trailers: vec![],
+        keep_alive: true,
+      })),
+      Assembled::Message(Message::Request(Request {
+        method: Methods::GET,
+        method_name: "GET".into(),
+        url: "/second?query".into(),
+        version: "1.1".into(),
+        headers: vec![header("Host", "localhost"), header("Connection", "close")],
+        body: vec![],
+        trailers: vec![],
+        keep_alive: false,
+      })),
+    ]
+  );
+}
+
+#[test]
+fn assembler_response_with_trailers() {
+  let mut assembler = MessageAssembler::new();
+
+  let input = http(
+    r#"
+      HTTP/1.1 200 OK\r\n
+      Transfer-Encoding: chunked\r\n
+      Trailer: x-checksum\r\n
+      \r\n
+      3\r\n
+      abc\r\n
+      2\r\n
+      de\r\n
+      0\r\n
+      x-checksum: 123\r\n
+      \r\n
+    "#,
+  );
+
+  assembler.feed(input.as_bytes()).unwrap();
+
+  assert_eq!(
+    drain(&mut assembler),
+    [Assembled::Message(Message::Response(Response {
+      status: 200,
+      reason: "OK".into(),
+      version: "1.1".into(),
+      headers: vec![header("Transfer-Encoding", "chunked"), header("Trailer", "x-checksum")],
+      body: b"abcde".to_vec(),
+      trailers: vec![header("x-checksum", "123")],
+      keep_alive: true,
+    }))]
+  );
+}
+
+#[test]
+fn assembler_streams_body_with_max_body_payload() {
+  let mut assembler = MessageAssembler::new();
+  assembler.parser_mut().max_body_payload = 4;
+
+  let input = http(
+    r#"
+      HTTP/1.1 200 OK\r\n
+      Content-Length: 10\r\n
+      \r\n
+      0123456789
+    "#,
+  );
+
+  assembler.feed(input.as_bytes()).unwrap();
+
+  let items = drain(&mut assembler);
+
+  assert!(
+    matches!(&items[0], Assembled::Head(Message::Response(Response { status: 200, body, .. })) if body.is_empty())
+  );
+  assert_eq!(
+    &items[1..],
+    [
+      Assembled::Body(b"0123".to_vec()),
+      Assembled::Body(b"4567".to_vec()),
+      Assembled::Body(b"89".to_vec()),
+      Assembled::End { trailers: vec![] },
+    ]
+  );
+}
+
+#[test]
+fn assembler_finish_completes_eof_delimited_body() {
+  let mut assembler = MessageAssembler::new();
+
+  assembler.feed(b"HTTP/1.1 200 OK\r\n\r\nabc").unwrap();
+  assert_eq!(assembler.next_item(), None);
+
+  assembler.finish().unwrap();
+
+  let Some(Assembled::Message(Message::Response(response))) = assembler.next_item() else {
+    panic!("Expected a response");
+  };
+
+  assert_eq!(response.body, b"abc");
+}
+
+#[test]
+fn assembler_error() {
+  let mut assembler = MessageAssembler::new();
+
+  let error = assembler.feed(b"GET / HTTP/1.1\r\nHo st: value\r\n\r\n").unwrap_err();
+
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(assembler.parser().error_code, ERROR_UNEXPECTED_CHARACTER);
+  assert_eq!(assembler.next_item(), None);
+}
+
+#[test]
+fn assembler_tunnel() {
+  let mut assembler = MessageAssembler::new();
+  assembler
+    .feed(b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\nraw bytes")
+    .unwrap();
+
+  // The message is produced even if it does not complete
+  let Some(Assembled::Message(Message::Request(request))) = assembler.next_item() else {
+    panic!("Expected a request");
+  };
+
+  assert_eq!(request.method, Methods::CONNECT);
+  assert_eq!(request.url, "example.com:443");
+  assert_eq!(assembler.parser().state, STATE_TUNNEL);
+  assert_eq!(assembler.parser().unconsumed_len, 9);
+
+  let mut assembler = MessageAssembler::new();
+  assembler
+    .feed(b"HTTP/1.1 101 Switching Protocols\r\nConnection: upgrade\r\nUpgrade: websocket\r\n\r\nframe")
+    .unwrap();
+
+  let Some(Assembled::Message(Message::Response(response))) = assembler.next_item() else {
+    panic!("Expected a response");
+  };
+
+  assert_eq!(response.status, 101);
+  assert_eq!(assembler.next_item(), None);
+  assert_eq!(assembler.parser().state, STATE_TUNNEL);
+
+  // Tunneled data is retained even if the parser was emitting it
+  let mut parser = Parser::new();
+  parser.emit_tunnel_data = true;
+
+  let mut assembler = MessageAssembler::with_parser(parser);
+  assembler
+    .feed(b"CONNECT example.com:443 HTTP/1.1\r\n\r\nraw bytes")
+    .unwrap();
+
+  assert!(assembler.next_item().is_some());
+  assert!(!assembler.parser().emit_tunnel_data);
+  assert_eq!(assembler.parser().unconsumed_len, 9);
+  assert_eq!(assembler.parser().tunnel_bytes, 0);
+}
+
+#[test]
+fn assembler_unknown_method() {
+  let mut assembler = MessageAssembler::new();
+  assembler.feed(b"PURGE /cache HTTP/1.1\r\n\r\n").unwrap();
+
+  let Some(Assembled::Message(Message::Request(request))) = assembler.next_item() else {
+    panic!("Expected a request");
+  };
+
+  assert_eq!(request.method, Methods::OTHER);
+  assert_eq!(request.method_name, "PURGE");
+}
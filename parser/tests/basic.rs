@@ -551,25 +551,25 @@ fn basic_pause_and_resume() {
   );
   let sample2 = http(r#"\r\nabc"#);
 
-  assert_eq!(parser.paused, false);
+  assert!(!parser.paused);
 
   let consumed1 = parse(&mut parser, &sample1);
   assert_eq!(consumed1, sample1.len());
 
-  assert_eq!(parser.paused, false);
+  assert!(!parser.paused);
   parser.pause();
-  assert_eq!(parser.paused, true);
+  assert!(parser.paused);
 
   let consumed3 = parse(&mut parser, &sample2);
   assert_eq!(consumed3, 0);
 
-  assert_eq!(parser.paused, true);
+  assert!(parser.paused);
   parser.resume();
-  assert_eq!(parser.paused, false);
+  assert!(!parser.paused);
 
   let consumed4 = parse(&mut parser, &sample2);
   assert_eq!(consumed4, sample2.len());
-  assert_eq!(parser.paused, false);
+  assert!(!parser.paused);
 
   assert_ne!(parser.state, STATE_ERROR);
 }
@@ -0,0 +1,366 @@
+mod helpers;
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use milo_parser::{CodecError, Errors, Frame, Header, HttpCodec, Message, Methods, Parser, Request, Response};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, duplex};
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+use crate::helpers::http;
+
+async fn decode_all(chunks: Vec<String>) -> Vec<Result<Frame, CodecError>> {
+  let (mut client, server) = duplex(16);
+
+  let writer = tokio::spawn(async move {
+    for chunk in chunks {
+      client.write_all(chunk.as_bytes()).await.unwrap();
+    }
+  });
+
+  let frames = FramedRead::new(server, HttpCodec::new()).collect().await;
+  writer.await.unwrap();
+  frames
+}
+
+fn frames(results: Vec<Result<Frame, CodecError>>) -> Vec<Frame> { results.into_iter().map(Result::unwrap).collect() }
+
+fn header(name: &str, value: &str) -> Header {
+  Header {
+    name: name.into(),
+    value: value.into(),
+  }
+}
+
+#[tokio::test]
+async fn codec_decodes_pipelined_requests() {
+  let input = http(
+    r#"
+      POST /upload HTTP/1.1\r\n
+      Transfer-Encoding: chunked\r\n
+      Trailer: x-checksum\r\n
+      \r\n
+      5\r\n
+      hello\r\n
+      0\r\n
+      x-checksum: abc\r\n
+      \r\n
+      GET / HTTP/1.1\r\n
+      Host: localhost\r\n
+      \r\n
+    "#,
+  );
+
+  // Split the input in tiny chunks to exercise the buffering
+  let chunks = input
+    .as_bytes()
+    .chunks(7)
+    .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+    .collect();
+  let frames = frames(decode_all(chunks).await);
+
+  let Frame::Head(Message::Request(request)) = &frames[0] else {
+    panic!("Expected a request head, got {:?}", frames[0]);
+  };
+
+  assert_eq!(request.method, Methods::POST);
+  assert_eq!(request.url, "/upload");
+
+  let body: Vec<u8> = frames
+    .iter()
+    .filter_map(|frame| {
+      match frame {
+        Frame::BodyChunk(chunk) => Some(chunk.to_vec()),
+        _ => None,
+      }
+    })
+    .flatten()
+    .collect();
+
+  assert_eq!(body, b"hello");
+
+  let rest: Vec<_> = frames
+    .iter()
+    .filter(|frame| !matches!(frame, Frame::BodyChunk(_)))
+    .skip(1)
+    .collect();
+  assert_eq!(rest[0], &Frame::Trailers(vec![header("x-checksum", "abc")]));
+  assert_eq!(rest[1], &Frame::End);
+  assert!(matches!(rest[2], Frame::Head(Message::Request(Request { url, .. })) if url == "/"));
+  assert_eq!(rest[3], &Frame::End);
+  assert_eq!(rest.len(), 4);
+}
+
+#[tokio::test]
+async fn codec_completes_body_delimited_by_eof() {
+  let frames = frames(decode_all(vec!["HTTP/1.1 200 OK\r\n\r\n".into(), "abc".into()]).await);
+
+  assert!(matches!(
+    &frames[0],
+    Frame::Head(Message::Response(Response { status: 200, .. }))
+  ));
+  assert_eq!(frames[1], Frame::BodyChunk(Bytes::from_static(b"abc")));
+  assert_eq!(frames[2], Frame::End);
+  assert_eq!(frames.len(), 3);
+}
+
+#[tokio::test]
+async fn codec_switches_to_tunnel() {
+  let frames = frames(
+    decode_all(vec![
+      "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\nraw ".into(),
+      "bytes".into(),
+    ])
+    .await,
+  );
+
+  assert!(matches!(
+    &frames[0],
+    Frame::Head(Message::Request(Request {
+      method: Methods::CONNECT,
+      ..
+    }))
+  ));
+  assert_eq!(frames[1], Frame::End);
+
+  let tunneled: Vec<u8> = frames[2..]
+    .iter()
+    .flat_map(|frame| {
+      match frame {
+        Frame::Tunnel(data) => data.to_vec(),
+        frame => panic!("Unexpected frame {frame:?}"),
+      }
+    })
+    .collect();
+
+  assert_eq!(tunneled, b"raw bytes");
+
+  // Tunneled data is never consumed by the parser
+  let mut parser = Parser::new();
+  parser.emit_tunnel_data = true;
+
+  let mut codec = HttpCodec::with_parser(parser);
+  let mut buffer = BytesMut::from(&b"CONNECT example.com:443 HTTP/1.1\r\n\r\nraw bytes"[..]);
+
+  assert!(matches!(codec.decode(&mut buffer).unwrap(), Some(Frame::Head(_))));
+  assert_eq!(codec.decode(&mut buffer).unwrap(), Some(Frame::End));
+  assert_eq!(
+    codec.decode(&mut buffer).unwrap(),
+    Some(Frame::Tunnel(Bytes::from_static(b"raw bytes")))
+  );
+
+  // Data is passed through as is once in tunnel mode
+  let mut codec = HttpCodec::new();
+  let mut buffer =
+    BytesMut::from(&b"GET /chat HTTP/1.1\r\nConnection: upgrade\r\nUpgrade: websocket\r\n\r\n\x81\x00"[..]);
+
+  assert!(matches!(codec.decode(&mut buffer).unwrap(), Some(Frame::Head(_))));
+  assert_eq!(codec.decode(&mut buffer).unwrap(), Some(Frame::End));
+  assert!(codec.is_tunnel());
+  assert_eq!(
+    codec.decode(&mut buffer).unwrap(),
+    Some(Frame::Tunnel(Bytes::from_static(b"\x81\x00")))
+  );
+  assert_eq!(codec.decode(&mut buffer).unwrap(), None);
+}
+
+#[tokio::test]
+async fn codec_errors() {
+  let results = decode_all(vec!["GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n".into()]).await;
+  assert!(matches!(&results[0], Err(CodecError::Parse(error)) if error.code == Errors::INVALID_CONTENT_LENGTH));
+
+  let results = decode_all(vec!["GET / HTTP/1.1\r\nHost".into()]).await;
+  assert!(matches!(&results[0], Err(CodecError::Parse(error)) if error.code == Errors::UNEXPECTED_EOF));
+}
+
+#[tokio::test]
+async fn codec_encodes_heads() {
+  let (client, mut server) = duplex(1024);
+  let mut writer = FramedWrite::new(client, HttpCodec::new());
+
+  writer
+    .send(Message::Request(Request {
+      method: Methods::PUT,
+      method_name: "PUT".into(),
+      url: "/item".into(),
+      version: "1.1".into(),
+      headers: vec![header("Content-Length", "2")],
+      body: Vec::new(),
+      trailers: Vec::new(),
+      keep_alive: true,
+    }))
+    .await
+    .unwrap();
+
+  writer
+    .send(Message::Response(Response {
+      status: 204,
+      reason: "No Content".into(),
+      ..Response::default()
+    }))
+    .await
+    .unwrap();
+
+  // Unknown methods are encoded using their name
+  writer
+    .send(Message::Request(Request {
+      method: Methods::OTHER,
+      method_name: "PURGE".into(),
+      url: "/cache".into(),
+      version: "1.1".into(),
+      headers: Vec::new(),
+      body: Vec::new(),
+      trailers: Vec::new(),
+      keep_alive: true,
+    }))
+    .await
+    .unwrap();
+
+  let error = writer
+    .send(Message::Request(Request {
+      method: Methods::OTHER,
+      method_name: String::new(),
+      url: "/".into(),
+      version: "1.1".into(),
+      headers: Vec::new(),
+      body: Vec::new(),
+      trailers: Vec::new(),
+      keep_alive: true,
+    }))
+    .await
+    .unwrap_err();
+
+  assert!(matches!(error, CodecError::InvalidMethod(Methods::OTHER)));
+  drop(writer);
+
+  let mut output = String::new();
+  server.read_to_string(&mut output).await.unwrap();
+
+  assert_eq!(
+    output,
+    "PUT /item HTTP/1.1\r\nContent-Length: 2\r\n\r\nHTTP/1.1 204 No Content\r\n\r\nPURGE /cache HTTP/1.1\r\n\r\n"
+  );
+}
+
+#[test]
+fn codec_rejects_invalid_heads() {
+  let request = |url: &str, headers: Vec<Header>| {
+    Message::Request(Request {
+      method: Methods::GET,
+      method_name: "GET".into(),
+      url: url.into(),
+      version: "1.1".into(),
+      headers,
+      body: Vec::new(),
+      trailers: Vec::new(),
+      keep_alive: true,
+    })
+  };
+
+  let mut codec = HttpCodec::new();
+  let mut dst = BytesMut::new();
+
+  let error = codec
+    .encode(request("/", vec![header("Bad Name", "value")]), &mut dst)
+    .unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidHeaderName(name) if name == "Bad Name"));
+
+  let error = codec
+    .encode(request("/", vec![header("X-Injected", "a\r\nSet-Cookie: b")]), &mut dst)
+    .unwrap_err();
+  assert!(matches!(error, CodecError::InvalidField("header value")));
+
+  let error = codec.encode(request("/\0", Vec::new()), &mut dst).unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidUrl(url) if url == "/\0"));
+  assert_eq!(error.to_string(), "Cannot encode URL \"/\\0\"");
+
+  let error = codec.encode(request("", Vec::new()), &mut dst).unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidUrl(url) if url.is_empty()));
+
+  let error = codec.encode(request("/a b", Vec::new()), &mut dst).unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidUrl(url) if url == "/a b"));
+
+  let error = codec
+    .encode(
+      Message::Request(Request {
+        method: Methods::GET,
+        method_name: "GET".into(),
+        url: "/".into(),
+        version: "1.1 X".into(),
+        headers: Vec::new(),
+        body: Vec::new(),
+        trailers: Vec::new(),
+        keep_alive: true,
+      }),
+      &mut dst,
+    )
+    .unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidVersion(version) if version == "1.1 X"));
+
+  let error = codec
+    .encode(
+      Message::Request(Request {
+        method: Methods::OTHER,
+        method_name: "GET /".into(),
+        url: "/".into(),
+        version: "1.1".into(),
+        headers: Vec::new(),
+        body: Vec::new(),
+        trailers: Vec::new(),
+        keep_alive: true,
+      }),
+      &mut dst,
+    )
+    .unwrap_err();
+  assert!(matches!(error, CodecError::InvalidMethod(Methods::OTHER)));
+
+  let error = codec
+    .encode(
+      Message::Response(Response {
+        status: 200,
+        reason: "OK\n".into(),
+        ..Response::default()
+      }),
+      &mut dst,
+    )
+    .unwrap_err();
+  assert!(matches!(error, CodecError::InvalidField("reason")));
+
+  let error = codec
+    .encode(
+      Message::Response(Response {
+        status: 200,
+        version: "11".into(),
+        ..Response::default()
+      }),
+      &mut dst,
+    )
+    .unwrap_err();
+  assert!(matches!(&error, CodecError::InvalidVersion(version) if version == "11"));
+
+  for status in [0, 99, 1000] {
+    let error = codec
+      .encode(
+        Message::Response(Response {
+          status,
+          ..Response::default()
+        }),
+        &mut dst,
+      )
+      .unwrap_err();
+    assert!(matches!(error, CodecError::InvalidStatus(value) if value == status));
+  }
+
+  let error = codec
+    .encode(
+      Message::Response(Response {
+        status: 1000,
+        ..Response::default()
+      }),
+      &mut dst,
+    )
+    .unwrap_err();
+  assert_eq!(error.to_string(), "Cannot encode status 1000");
+
+  // Nothing is written for rejected messages
+  assert!(dst.is_empty());
+}
@@ -0,0 +1,111 @@
+mod helpers;
+
+use milo_parser::{
+  ConfigError, LENIENT_ALL, MESSAGE_TYPE_REQUEST, MESSAGE_TYPE_RESPONSE, PROTOCOL_ICE, Parser, ParserConfig,
+  STATE_ERROR,
+};
+
+use crate::helpers::http;
+
+#[test]
+fn config_builder_validation() {
+  let config = ParserConfig::builder()
+    .max_header_length(1024)
+    .message_type(MESSAGE_TYPE_REQUEST)
+    .build()
+    .unwrap();
+
+  assert_eq!(config.max_header_length, 1024);
+  assert_eq!(config.max_start_line_length, 8192);
+  assert_eq!(config.message_type, MESSAGE_TYPE_REQUEST);
+
+  assert_eq!(
+    ParserConfig::builder().max_start_line_length(0).build(),
+    Err(ConfigError::InvalidMaxStartLineLength)
+  );
+  assert_eq!(
+    ParserConfig::builder().max_header_length(0).build(),
+    Err(ConfigError::InvalidMaxHeaderLength)
+  );
+  assert_eq!(
+    ParserConfig::builder().message_type(3).build(),
+    Err(ConfigError::InvalidMessageType)
+  );
+  assert_eq!(
+    ParserConfig::builder().max_unconsumed_length(10).build(),
+    Err(ConfigError::UnconsumedLimitWithoutManagement)
+  );
+  assert_eq!(
+    ParserConfig::builder().lenient_flags(LENIENT_ALL + 1).build(),
+    Err(ConfigError::InvalidLenientFlags)
+  );
+  assert_eq!(
+    ParserConfig::builder().protocol(PROTOCOL_ICE + 1).build(),
+    Err(ConfigError::InvalidProtocol)
+  );
+
+  let config = ParserConfig {
+    message_type: 10,
+    ..ParserConfig::default()
+  };
+
+  assert_eq!(Parser::with_config(config).err(), Some(ConfigError::InvalidMessageType));
+}
+
+#[test]
+fn config_is_applied() {
+  let config = ParserConfig::builder()
+    .message_type(MESSAGE_TYPE_RESPONSE)
+    .suspend_after_headers(true)
+    .manage_unconsumed(true)
+    .max_unconsumed_length(100)
+    .build()
+    .unwrap();
+
+  let parser = Parser::with_config(config).unwrap();
+
+  assert!(!parser.autodetect);
+  assert!(!parser.is_request);
+  assert!(parser.suspend_after_headers);
+  assert!(parser.manage_unconsumed);
+  assert_eq!(parser.max_unconsumed_length, 100);
+}
+
+#[test]
+fn config_is_reapplied_on_reset() {
+  let config = ParserConfig::builder()
+    .message_type(MESSAGE_TYPE_REQUEST)
+    .build()
+    .unwrap();
+
+  // The same configuration can be shared by many parsers
+  let mut parsers: Vec<_> = (0..3).map(|_| Parser::with_config(config).unwrap()).collect();
+  let request = http(r"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+
+  for parser in parsers.iter_mut() {
+    parser.skip_body = true;
+    parser.max_header_length = 1;
+    parser.is_request = false;
+
+    parser.reset(false);
+
+    assert!(!parser.skip_body);
+    assert_eq!(parser.max_header_length, 8192);
+    assert!(parser.is_request);
+
+    parser.parse(request.as_ptr(), request.len());
+    assert_ne!(parser.state, STATE_ERROR);
+  }
+}
+
+#[test]
+fn config_is_not_applied_without_config() {
+  let mut parser = Parser::new();
+  parser.autodetect = false;
+  parser.is_request = true;
+
+  parser.reset(false);
+
+  assert!(!parser.autodetect);
+  assert!(parser.is_request);
+}
@@ -0,0 +1,118 @@
+mod helpers;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_CONNECT, EVENT_ACTIVE_ON_DATA, EVENT_ACTIVE_ON_MESSAGE_COMPLETE, Errors, Event, Parser, STATE_ERROR,
+  STATE_START, STATE_TUNNEL,
+};
+
+use crate::helpers::http;
+
+fn response_parser() -> Parser {
+  let mut parser = Parser::new();
+  parser.autodetect = false;
+  parser.is_request = false;
+  parser.expect_connect_response = true;
+  parser.active_events = EVENT_ACTIVE_ON_CONNECT | EVENT_ACTIVE_ON_DATA | EVENT_ACTIVE_ON_MESSAGE_COMPLETE;
+  parser
+}
+
+fn parse(parser: &mut Parser, input: &str) -> Vec<String> {
+  parser
+    .parse_slice(input.as_bytes())
+    .map(|event| {
+      match event {
+        Event::Range { event, data, .. } => format!("{:?}: {}", event, String::from_utf8_lossy(data)),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect()
+}
+
+#[test]
+fn connect_response_tunnel() {
+  let mut parser = response_parser();
+
+  let input = "HTTP/1.1 200 Connection Established\r\n\r\n\x16\x03\x01";
+  let consumed = parser.parse(input.as_ptr(), input.len());
+
+  assert_eq!(consumed, input.len() - 3);
+  assert_eq!(parser.state, STATE_TUNNEL);
+  assert!(parser.is_connect);
+  assert!(!parser.expect_connect_response);
+
+  // Any successful status is accepted
+  let mut parser = response_parser();
+  assert_eq!(parse(&mut parser, "HTTP/1.1 204 No Content\r\n\r\n"), ["CONNECT: "]);
+  assert_eq!(parser.state, STATE_TUNNEL);
+}
+
+#[test]
+fn connect_response_failed() {
+  let mut parser = response_parser();
+
+  let input = http(
+    r#"
+      HTTP/1.1 407 Proxy Authentication Required\r\n
+      Content-Length: 4\r\n
+      \r\n
+      deny
+      HTTP/1.1 200 OK\r\n
+      Content-Length: 2\r\n
+      \r\n
+      ok
+    "#,
+  );
+
+  // Only the final response to the CONNECT request is affected
+  assert_eq!(
+    parse(&mut parser, &input),
+    ["DATA: deny", "MESSAGE_COMPLETE: ", "DATA: ok", "MESSAGE_COMPLETE: "]
+  );
+  assert_eq!(parser.state, STATE_START);
+  assert!(!parser.is_connect);
+}
+
+#[test]
+fn connect_response_informational() {
+  let mut parser = response_parser();
+
+  let input = "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\r\n";
+  assert_eq!(parse(&mut parser, input), ["MESSAGE_COMPLETE: ", "CONNECT: "]);
+  assert_eq!(parser.state, STATE_TUNNEL);
+}
+
+#[test]
+fn connect_response_errors() {
+  for (headers, code, description) in [
+    (
+      "Content-Length: 0\r\n",
+      Errors::UNEXPECTED_CONTENT_LENGTH,
+      "Unexpected Content-Length header in a successful response to CONNECT",
+    ),
+    (
+      "Transfer-Encoding: chunked\r\n",
+      Errors::UNEXPECTED_TRANSFER_ENCODING,
+      "Unexpected Transfer-Encoding header in a successful response to CONNECT",
+    ),
+  ] {
+    let mut parser = response_parser();
+    let input = format!("HTTP/1.1 200 OK\r\n{headers}\r\n");
+    parser.parse(input.as_ptr(), input.len());
+
+    let error = parser.error().unwrap();
+    assert_eq!(error.code, code, "{}", headers);
+    assert_eq!(error.description, description, "{}", headers);
+  }
+}
+
+#[test]
+fn connect_response_cleared() {
+  let mut parser = response_parser();
+  parser.clear();
+  assert!(!parser.expect_connect_response);
+
+  // Without the flag, the response is read until the connection is closed
+  assert_eq!(parse(&mut parser, "HTTP/1.1 200 OK\r\n\r\ndata"), ["DATA: data"]);
+  assert_ne!(parser.state, STATE_ERROR);
+  assert_ne!(parser.state, STATE_TUNNEL);
+}
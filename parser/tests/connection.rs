@@ -0,0 +1,164 @@
+mod helpers;
+
+use milo_parser::{
+  ClientConnection, EVENT_ACTIVE_ON_DATA, EVENT_ACTIVE_ON_HEADERS, EVENT_ACTIVE_ON_MESSAGE_COMPLETE, Errors, Event,
+  Events, Methods, STATE_TUNNEL,
+};
+
+use crate::helpers::http;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Exchange {
+  request: Option<u64>,
+  status: u16,
+  body: String,
+  complete: bool,
+}
+
+// Parses all the data, returning the responses and the unconsumed data
+fn run<'a>(connection: &mut ClientConnection, data: &'a [u8]) -> (Vec<Exchange>, &'a [u8]) {
+  connection.parser_mut().active_events =
+    EVENT_ACTIVE_ON_HEADERS | EVENT_ACTIVE_ON_DATA | EVENT_ACTIVE_ON_MESSAGE_COMPLETE;
+
+  let mut responses: Vec<Exchange> = Vec::new();
+  let mut input = data;
+
+  loop {
+    let consumed = connection.parse(input);
+    let request = connection.current_request().map(|request| request.id);
+
+    for event in connection.parser().iter_events(input) {
+      match event {
+        Event::Headers(metadata) => {
+          responses.push(Exchange {
+            request,
+            status: metadata.status_or_method,
+            ..Exchange::default()
+          })
+        }
+        Event::Range {
+          event: Events::DATA,
+          data,
+          ..
+        } => {
+          responses
+            .last_mut()
+            .unwrap()
+            .body
+            .push_str(&String::from_utf8_lossy(data))
+        }
+        Event::Range {
+          event: Events::MESSAGE_COMPLETE,
+          ..
+        } => responses.last_mut().unwrap().complete = true,
+        _ => (),
+      }
+    }
+
+    input = &input[consumed..];
+
+    if connection.parser().error().is_some()
+      || connection.parser().state == STATE_TUNNEL
+      || (consumed == 0 && !connection.parser().continue_without_data)
+    {
+      return (responses, input);
+    }
+  }
+}
+
+fn exchange(request: u64, status: u16, body: &str) -> Exchange {
+  Exchange {
+    request: Some(request),
+    status,
+    body: body.into(),
+    complete: true,
+  }
+}
+
+#[test]
+fn connection_pairs_pipelined_responses() {
+  let mut connection = ClientConnection::new();
+  assert_eq!(connection.send(Methods::GET), 0);
+  assert_eq!(connection.send(Methods::HEAD), 1);
+  assert_eq!(connection.send(Methods::POST), 2);
+
+  let input = http(
+    r#"
+      HTTP/1.1 200 OK\r\n
+      Content-Length: 3\r\n
+      \r\n
+      abc
+      HTTP/1.1 200 OK\r\n
+      Content-Length: 1000\r\n
+      \r\n
+      HTTP/1.1 100 Continue\r\n
+      \r\n
+      HTTP/1.1 201 Created\r\n
+      Content-Length: 2\r\n
+      \r\n
+      ok
+    "#,
+  );
+
+  let (responses, rest) = run(&mut connection, input.as_bytes());
+
+  // The HEAD response body is skipped and the informational response does not
+  // complete the request
+  assert_eq!(
+    responses,
+    [
+      exchange(0, 200, "abc"),
+      exchange(1, 200, ""),
+      exchange(2, 100, ""),
+      exchange(2, 201, "ok"),
+    ]
+  );
+  assert!(rest.is_empty());
+  assert_eq!(connection.pending(), 0);
+}
+
+#[test]
+fn connection_tunnels_after_successful_connect() {
+  let mut connection = ClientConnection::new();
+  connection.send(Methods::CONNECT);
+
+  let input = b"HTTP/1.1 200 Connection Established\r\n\r\nraw data";
+  let (responses, rest) = run(&mut connection, input);
+
+  assert_eq!(responses[0].request, Some(0));
+  assert_eq!(connection.parser().state, STATE_TUNNEL);
+  assert_eq!(rest, b"raw data");
+
+  // A failed CONNECT is a regular response
+  let mut connection = ClientConnection::new();
+  connection.send(Methods::CONNECT);
+  connection.send(Methods::GET);
+
+  let input = http(
+    r#"
+      HTTP/1.1 407 Proxy Authentication Required\r\n
+      Content-Length: 4\r\n
+      \r\n
+      deny
+      HTTP/1.1 204 No Content\r\n
+      \r\n
+    "#,
+  );
+
+  let (responses, rest) = run(&mut connection, input.as_bytes());
+
+  assert_eq!(responses, [exchange(0, 407, "deny"), exchange(1, 204, "")]);
+  assert!(rest.is_empty());
+}
+
+#[test]
+fn connection_rejects_unexpected_responses() {
+  let mut connection = ClientConnection::new();
+  connection.send(Methods::GET);
+
+  let input = b"HTTP/1.1 204 No Content\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n";
+  let (responses, _) = run(&mut connection, input);
+
+  assert_eq!(responses[0], exchange(0, 204, ""));
+  assert_eq!(connection.parser().error().unwrap().code, Errors::UNEXPECTED_RESPONSE);
+}
@@ -0,0 +1,104 @@
+mod helpers;
+
+use milo_parser::{CALLBACK_ACTIVE_ON_DATA, CALLBACK_ACTIVE_ON_URL, Parser};
+
+use crate::helpers::http;
+
+#[derive(Debug, Default, PartialEq)]
+struct Captured {
+  url: String,
+  body: Vec<u8>,
+}
+
+#[test]
+fn context_typed_accessors() {
+  let mut parser = Parser::new();
+
+  assert!(parser.context::<Captured>().is_none());
+
+  parser.set_context(Captured::default());
+  assert!(parser.context::<String>().is_none());
+  assert!(parser.context_mut::<String>().is_none());
+  assert!(parser.take_context::<String>().is_none());
+
+  parser.context_mut::<Captured>().unwrap().url.push_str("/url");
+  assert_eq!(parser.context::<Captured>().unwrap().url, "/url");
+
+  let captured = parser.take_context::<Captured>().unwrap();
+  assert_eq!(captured.url, "/url");
+  assert!(parser.context::<Captured>().is_none());
+  assert!(parser.context.is_null());
+}
+
+#[test]
+fn context_is_available_in_callbacks() {
+  struct Context {
+    input: Vec<u8>,
+    captured: Captured,
+  }
+
+  fn on_url(parser: &mut Parser, at: usize, len: usize) {
+    let context = parser.context_mut::<Context>().unwrap();
+    context.captured.url = String::from_utf8(context.input[at..at + len].to_vec()).unwrap();
+  }
+
+  fn on_data(parser: &mut Parser, at: usize, len: usize) {
+    let context = parser.context_mut::<Context>().unwrap();
+    let data = context.input[at..at + len].to_vec();
+    context.captured.body.extend_from_slice(&data);
+  }
+
+  let request = http(
+    r#"
+        POST /url HTTP/1.1\r\n
+        Content-Length: 3\r\n
+        \r\n
+        abc
+      "#,
+  );
+
+  let mut parser = Parser::new();
+  parser.callbacks.on_url = on_url;
+  parser.callbacks.on_data = on_data;
+  parser.active_callbacks = CALLBACK_ACTIVE_ON_URL | CALLBACK_ACTIVE_ON_DATA;
+  parser.set_context(Context {
+    input: request.as_bytes().to_vec(),
+    captured: Captured::default(),
+  });
+
+  parser.parse(request.as_ptr(), request.len());
+
+  assert_eq!(
+    parser.take_context::<Context>().unwrap().captured,
+    Captured {
+      url: "/url".into(),
+      body: b"abc".to_vec()
+    }
+  );
+}
+
+#[test]
+fn context_is_dropped_with_the_parser() {
+  use std::rc::Rc;
+
+  let shared = Rc::new(());
+
+  let mut parser = Parser::new();
+  parser.set_context(Rc::clone(&shared));
+  assert_eq!(Rc::strong_count(&shared), 2);
+
+  parser.set_context(Rc::clone(&shared));
+  assert_eq!(Rc::strong_count(&shared), 2);
+
+  drop(parser);
+  assert_eq!(Rc::strong_count(&shared), 1);
+}
+
+#[test]
+fn context_keeps_field_offsets() {
+  use std::mem::{offset_of, size_of};
+
+  // The owned context follows the fields read by the C++ and WebAssembly APIs
+  assert_eq!(offset_of!(Parser, max_start_line_length), size_of::<*mut ()>());
+  assert!(offset_of!(Parser, owned_context) > offset_of!(Parser, events));
+}
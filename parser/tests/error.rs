@@ -0,0 +1,65 @@
+mod helpers;
+
+use std::error::Error;
+
+use milo_parser::{Errors, ParseError, Parser, States};
+
+use crate::helpers::http;
+
+#[test]
+fn error_try_parse_ok() {
+  let mut parser = Parser::new();
+  let request = http(r"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+
+  assert_eq!(parser.try_parse(request.as_bytes()), Ok(request.len()));
+  assert!(parser.error().is_none());
+}
+
+#[test]
+fn error_try_parse_failure() {
+  let mut parser = Parser::new();
+  let first = http(r"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n");
+  let second = http(r"GET / HTTP/1.1\r\nHo st: localhost\r\n\r\n");
+
+  assert_eq!(parser.try_parse(first.as_bytes()), Ok(first.len()));
+
+  let error = parser.try_parse(second.as_bytes()).unwrap_err();
+
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.description, "Invalid header field name character");
+  assert_eq!(error.position, 16);
+  assert_eq!(error.offset, (first.len() + 16) as u64);
+  assert_eq!(error.state, States::HEADER);
+  assert_eq!(parser.error(), Some(error.clone()));
+
+  assert_eq!(
+    error.to_string(),
+    format!(
+      "UNEXPECTED_CHARACTER at offset {} (state HEADER): Invalid header field name character",
+      first.len() + 16
+    )
+  );
+
+  parser.reset(false);
+  assert!(parser.error().is_none());
+}
+
+#[test]
+fn error_composes_with_std_error() {
+  fn parse(input: &[u8]) -> Result<usize, Box<dyn Error + Send + Sync>> { Ok(Parser::new().try_parse(input)?) }
+
+  let error = parse(b"GET / HTTP/1.1\r\nHost localhost\r\n\r\n").unwrap_err();
+  assert!(error.downcast_ref::<ParseError>().is_some());
+}
+
+#[test]
+fn error_custom_code() {
+  let mut parser = Parser::new();
+  parser.fail(200, "Custom failure");
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::USER);
+  assert_eq!(error.description, "Custom failure");
+  assert_eq!(error.state, States::START);
+  assert_eq!(parser.error_code_str(), "UNKNOWN");
+}
@@ -0,0 +1,221 @@
+mod helpers;
+
+use milo_parser::{
+  BodyKind, ERROR_UNEXPECTED_CHARACTER, EVENT_ACTIVE_ALL, EVENT_ACTIVE_ON_DATA, EVENT_ACTIVE_ON_ERROR,
+  EVENT_ACTIVE_ON_URL, EVENT_URL, EVENTS_MIN_BUFFER_SIZE, Errors, Event, Events, HeadersMetadata, METHOD_POST, Parser,
+};
+
+use crate::helpers::http;
+
+#[test]
+fn events_parse_slice_request() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ALL;
+
+  let request = http(
+    r#"
+        POST /url HTTP/1.1\r\n
+        Content-Length: 3\r\n
+        \r\n
+        abc
+      "#,
+  );
+
+  let events = parser.parse_slice(request.as_bytes());
+  assert_eq!(events.consumed(), request.len());
+
+  let events: Vec<_> = events.collect();
+  let ranges: Vec<_> = events
+    .iter()
+    .filter_map(|event| {
+      match event {
+        Event::Range { event, data, .. } => Some((*event, *data)),
+        Event::HeaderName { data, .. } => Some((Events::HEADER_NAME, *data)),
+        _ => None,
+      }
+    })
+    .collect();
+
+  assert_eq!(
+    ranges,
+    [
+      (Events::REQUEST, &b""[..]),
+      (Events::MESSAGE_START, b""),
+      (Events::METHOD, b"POST"),
+      (Events::URL, b"/url"),
+      (Events::PROTOCOL, b"HTTP"),
+      (Events::VERSION, b"1.1"),
+      (Events::HEADER_NAME, b"Content-Length"),
+      (Events::HEADER_VALUE, b"3"),
+      (Events::DATA, b"abc"),
+      (Events::BODY, b""),
+      (Events::MESSAGE_COMPLETE, b""),
+      (Events::RESET, b""),
+    ]
+  );
+
+  assert!(events.contains(&Event::Headers(HeadersMetadata {
+    at: 41,
+    status_or_method: METHOD_POST as u16,
+    should_keep_alive: true,
+    should_upgrade: false,
+    has_trailers: false,
+    body_kind: BodyKind::ContentLength,
+    content_length: 3,
+    version_minor: 1,
+  })));
+}
+
+#[test]
+fn events_parse_slice_only_returns_active_events() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_URL | EVENT_ACTIVE_ON_DATA;
+
+  let request = http(
+    r#"
+        POST /first HTTP/1.1\r\n
+        Content-Length: 3\r\n
+        \r\n
+        abc
+        GET /second HTTP/1.1\r\n
+        \r\n
+      "#,
+  );
+
+  let events: Vec<_> = parser.parse_slice(request.as_bytes()).collect();
+
+  assert_eq!(
+    events,
+    [
+      Event::Range {
+        event: Events::URL,
+        at: 5,
+        data: b"/first"
+      },
+      Event::Range {
+        event: Events::DATA,
+        at: 43,
+        data: b"abc"
+      },
+      Event::Range {
+        event: Events::URL,
+        at: 50,
+        data: b"/second"
+      },
+    ]
+  );
+}
+
+#[test]
+fn events_parse_slice_error() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_ERROR;
+
+  let events: Vec<_> = parser
+    .parse_slice(b"GET / HTTP/1.1\r\nHost : localhost\r\n\r\n")
+    .collect();
+
+  assert_eq!(
+    events,
+    [Event::Error {
+      at: 16,
+      code: Errors::UNEXPECTED_CHARACTER
+    }]
+  );
+  assert_eq!(parser.error_code, ERROR_UNEXPECTED_CHARACTER);
+}
+
+#[test]
+fn events_parse_slice_without_active_events() {
+  let mut parser = Parser::new();
+
+  let mut events = parser.parse_slice(b"GET / HTTP/1.1\r\n\r\n");
+
+  assert_eq!(events.consumed(), 18);
+  assert!(events.next().is_none());
+}
+
+#[test]
+fn events_custom_capacity_resumes_without_duplicates() {
+  let request = http(
+    r#"
+        POST /first HTTP/1.1\r\n
+        Content-Length: 3\r\n
+        \r\n
+        abc
+        GET /second HTTP/1.1\r\n
+        Header1: Value1\r\n
+        Header2: Value2\r\n
+        Header3: Value3\r\n
+        \r\n
+      "#,
+  );
+
+  let collect = |mut parser: Parser| {
+    parser.active_events = EVENT_ACTIVE_ALL;
+
+    let mut events = Vec::new();
+    let mut offset = 0;
+
+    while offset < request.len() || parser.continue_without_data {
+      let iterator = parser.parse_slice(&request.as_bytes()[offset..]);
+      let consumed = iterator.consumed();
+
+      events.extend(iterator.map(|event| {
+        match event {
+          Event::Range { event, at, data } => format!("{} {} {:?}", event.as_str(), offset + at, data),
+          Event::HeaderName { at, data, header } => {
+            format!("HEADER_NAME {} {:?} {}", offset + at, data, header.as_str())
+          }
+          Event::Headers(metadata) => format!("HEADERS {}", offset + metadata.at),
+          Event::StateChange { at, state } => format!("STATE_CHANGE {} {}", offset + at, state.as_str()),
+          other => format!("{:?}", other),
+        }
+      }));
+
+      offset += consumed;
+    }
+
+    events
+  };
+
+  let expected = collect(Parser::new());
+  let small = collect(Parser::with_events_capacity(EVENTS_MIN_BUFFER_SIZE));
+
+  assert_eq!(small, expected);
+}
+
+#[test]
+fn events_caller_owned_buffer() {
+  let mut buffer = [0xffu8; 512];
+
+  {
+    let mut parser = unsafe { Parser::with_events_buffer(buffer.as_mut_ptr(), buffer.len()) };
+    parser.active_events = EVENT_ACTIVE_ON_URL;
+
+    let events: Vec<_> = parser.parse_slice(b"GET /url HTTP/1.1\r\n\r\n").collect();
+
+    assert_eq!(
+      events,
+      [Event::Range {
+        event: Events::URL,
+        at: 4,
+        data: b"/url"
+      }]
+    );
+    assert_eq!(parser.events_capacity, 512);
+    assert!(!parser.owns_events);
+  }
+
+  assert_eq!(buffer[0], EVENT_URL);
+}
+
+#[test]
+#[should_panic(expected = "The events buffer capacity must be at least")]
+fn events_capacity_must_be_large_enough() { Parser::with_events_capacity(EVENTS_MIN_BUFFER_SIZE - 1); }
+
+#[test]
+#[should_panic(expected = "The events buffer must not be null")]
+fn events_buffer_must_not_be_null() {
+  unsafe { Parser::with_events_buffer(std::ptr::null_mut(), EVENTS_MIN_BUFFER_SIZE) };
+}
@@ -0,0 +1,217 @@
+mod helpers;
+
+use std::cell::RefCell;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_HEADERS, EVENT_ACTIVE_ON_HTTP2_SETTING, EVENT_HTTP2_SETTING, Errors, Event, Handler, Parser,
+  STATE_ERROR, STATE_TUNNEL,
+};
+
+use crate::helpers::http;
+
+// SETTINGS_MAX_CONCURRENT_STREAMS is 100 and SETTINGS_INITIAL_WINDOW_SIZE is
+// 65535
+fn request() -> String {
+  http(
+    r#"
+      GET / HTTP/1.1\r\n
+      Host: localhost\r\n
+      Connection: Upgrade, HTTP2-Settings\r\n
+      Upgrade: h2c\r\n
+      HTTP2-Settings: AAMAAABkAAQAAP__\r\n
+      \r\n
+    "#,
+  )
+}
+
+#[test]
+fn h2c_settings() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING;
+
+  let request = request();
+  let events: Vec<_> = parser.parse_slice(request.as_bytes()).collect();
+
+  assert_eq!(
+    events,
+    [
+      Event::Http2Setting {
+        at: 100,
+        data: b"AAMAAABk",
+        identifier: 3,
+        value: 100
+      },
+      Event::Http2Setting {
+        at: 108,
+        data: b"AAQAAP__",
+        identifier: 4,
+        value: 65535
+      },
+    ]
+  );
+
+  // Setting records carry the identifier and the value after the position
+  let record = unsafe { std::slice::from_raw_parts(parser.events, 11) };
+  assert_eq!(record[0], EVENT_HTTP2_SETTING);
+  assert_eq!(&record[5..11], [3, 0, 100, 0, 0, 0]);
+
+  assert!(parser.has_upgrade_h2c);
+  assert!(parser.has_http2_settings);
+  assert!(parser.has_connection_http2_settings);
+  assert_ne!(parser.state, STATE_ERROR);
+}
+
+#[test]
+fn h2c_upgrade() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING | EVENT_ACTIVE_ON_HEADERS;
+
+  let request = request();
+  let events: Vec<_> = parser
+    .parse_slice(request.as_bytes())
+    .map(|event| {
+      match event {
+        Event::Http2Setting { identifier, .. } => format!("setting {identifier}"),
+        Event::Headers(_) => "headers".into(),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect();
+
+  // Settings are available before the upgrade is performed
+  assert_eq!(events, ["setting 3", "setting 4", "headers"]);
+  assert_eq!(parser.state, STATE_TUNNEL);
+}
+
+thread_local! {
+  static RECEIVED: RefCell<Vec<(u16, u32)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_http2_setting(parser: &mut Parser, _at: usize, len: usize) {
+  assert_eq!(len, 0);
+  RECEIVED.with(|received| {
+    received
+      .borrow_mut()
+      .push((parser.http2_setting_identifier, parser.http2_setting_value))
+  });
+}
+
+#[test]
+fn h2c_callbacks_receive_settings() {
+  let mut parser = Parser::new();
+  parser.callbacks.on_http2_setting = on_http2_setting;
+  parser.active_callbacks = EVENT_ACTIVE_ON_HTTP2_SETTING;
+
+  let request = request();
+  parser.parse(request.as_ptr(), request.len());
+
+  assert_eq!(RECEIVED.take(), [(3, 100), (4, 65535)]);
+}
+
+#[derive(Default)]
+struct Recorder {
+  settings: Vec<(u16, u32)>,
+}
+
+impl Handler for Recorder {
+  fn on_http2_setting(&mut self, _parser: &mut Parser, identifier: u16, value: u32) {
+    self.settings.push((identifier, value));
+  }
+}
+
+#[test]
+fn h2c_handler_receives_settings() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING;
+
+  let mut recorder = Recorder::default();
+  parser.parse_with(request().as_bytes(), &mut recorder);
+
+  assert_eq!(recorder.settings, [(3, 100), (4, 65535)]);
+}
+
+#[test]
+fn h2c_empty_settings() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING;
+
+  let input = "GET / HTTP/1.1\r\nConnection: upgrade, http2-settings\r\nUpgrade: h2c\r\nHTTP2-Settings: \r\n\r\n";
+  parser.parse(input.as_ptr(), input.len());
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.description, "Expected HTTP2-Settings header value");
+}
+
+#[test]
+fn h2c_errors() {
+  for (headers, description) in [
+    (
+      "Connection: Upgrade\r\nUpgrade: h2c\r\n",
+      "Missing HTTP2-Settings header for a h2c upgrade",
+    ),
+    (
+      "Connection: Upgrade\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAABk\r\n",
+      "Missing Connection header set to \"HTTP2-Settings\" when using the HTTP2-Settings header",
+    ),
+    (
+      "Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAABk\r\nHTTP2-Settings: AAMAAABk\r\n",
+      "Invalid duplicate HTTP2-Settings header",
+    ),
+    (
+      "Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAAB\r\n",
+      "Invalid HTTP2-Settings header",
+    ),
+    (
+      "Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAAB+\r\n",
+      "Invalid HTTP2-Settings header",
+    ),
+    (
+      "Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: AAMAAAB=\r\n",
+      "Invalid HTTP2-Settings header",
+    ),
+    (
+      &format!(
+        "Connection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: {}\r\n",
+        "AAMAAABk".repeat(17)
+      ),
+      "Too many settings in HTTP2-Settings header",
+    ),
+  ] {
+    let mut parser = Parser::new();
+    parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING;
+    let input = format!("GET / HTTP/1.1\r\n{headers}\r\n");
+    parser.parse(input.as_ptr(), input.len());
+
+    let error = parser.error().unwrap();
+    assert_eq!(error.code, Errors::INVALID_HTTP2_SETTINGS, "{}", headers);
+    assert_eq!(error.description, description, "{}", headers);
+  }
+}
+
+#[test]
+fn h2c_many_settings_without_events() {
+  // The settings are only capped when they are reported
+  let mut parser = Parser::new();
+  let input = format!(
+    "GET / HTTP/1.1\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: {}\r\n\r\n",
+    "AAMAAABk".repeat(32)
+  );
+  parser.parse(input.as_ptr(), input.len());
+
+  assert!(parser.error().is_none());
+  assert!(parser.has_http2_settings);
+}
+
+#[test]
+fn h2c_settings_ignored_in_responses() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HTTP2_SETTING;
+
+  let input = "HTTP/1.1 200 OK\r\nHTTP2-Settings: invalid\r\nContent-Length: 0\r\n\r\n";
+  let events: Vec<_> = parser.parse_slice(input.as_bytes()).collect();
+
+  assert!(events.is_empty());
+  assert!(!parser.has_http2_settings);
+  assert_ne!(parser.state, STATE_ERROR);
+}
@@ -0,0 +1,130 @@
+mod helpers;
+
+use milo_parser::{
+  BodyKind, EVENT_ACTIVE_ALL, EVENT_ACTIVE_ON_ERROR, EVENT_ACTIVE_ON_URL, Errors, Handler, Headers, HeadersMetadata,
+  Methods, Parser,
+};
+
+use crate::helpers::http;
+
+#[derive(Default)]
+struct Recorder {
+  lines: Vec<String>,
+}
+
+impl Handler for Recorder {
+  fn on_method(&mut self, _parser: &mut Parser, data: &[u8]) {
+    self.lines.push(format!("method={}", String::from_utf8_lossy(data)));
+  }
+
+  fn on_url(&mut self, _parser: &mut Parser, data: &[u8]) {
+    self.lines.push(format!("url={}", String::from_utf8_lossy(data)));
+  }
+
+  fn on_header_name(&mut self, _parser: &mut Parser, data: &[u8], _header: Headers) {
+    self
+      .lines
+      .push(format!("header_name={}", String::from_utf8_lossy(data)));
+  }
+
+  fn on_header_value(&mut self, _parser: &mut Parser, data: &[u8]) {
+    self
+      .lines
+      .push(format!("header_value={}", String::from_utf8_lossy(data)));
+  }
+
+  fn on_headers(&mut self, _parser: &mut Parser, metadata: HeadersMetadata) {
+    self.lines.push(format!(
+      "headers body_kind={:?} content_length={}",
+      metadata.body_kind, metadata.content_length
+    ));
+  }
+
+  fn on_data(&mut self, _parser: &mut Parser, data: &[u8]) {
+    self.lines.push(format!("data={}", String::from_utf8_lossy(data)));
+  }
+
+  fn on_message_complete(&mut self, parser: &mut Parser, _data: &[u8]) {
+    self.lines.push(format!(
+      "complete method={}",
+      Methods::try_from(parser.method).unwrap().as_str()
+    ));
+  }
+
+  fn on_error(&mut self, _parser: &mut Parser, code: Errors) { self.lines.push(format!("error={}", code.as_str())); }
+}
+
+#[test]
+fn handler_parse_with() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ALL;
+  let mut recorder = Recorder::default();
+
+  let request = http(
+    r#"
+        POST /url HTTP/1.1\r\n
+        Content-Length: 3\r\n
+        \r\n
+        abc
+      "#,
+  );
+
+  let consumed = parser.parse_with(request.as_bytes(), &mut recorder);
+
+  assert_eq!(consumed, request.len());
+  assert_eq!(
+    recorder.lines,
+    [
+      "method=POST",
+      "url=/url",
+      "header_name=Content-Length",
+      "header_value=3",
+      &format!("headers body_kind={:?} content_length=3", BodyKind::ContentLength),
+      "data=abc",
+      "complete method=POST",
+    ]
+  );
+}
+
+#[test]
+fn handler_parse_with_only_dispatches_active_events() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_URL | EVENT_ACTIVE_ON_ERROR;
+  let mut recorder = Recorder::default();
+
+  parser.parse_with(
+    b"GET /first HTTP/1.1\r\n\r\nGET /second HTTP/1.1\r\nHost : example.com\r\n\r\n",
+    &mut recorder,
+  );
+
+  assert_eq!(
+    recorder.lines,
+    ["url=/first", "url=/second", "error=UNEXPECTED_CHARACTER"]
+  );
+}
+
+#[test]
+fn handler_closures_state() {
+  struct Counter<F: FnMut(&[u8])> {
+    on_body: F,
+  }
+
+  impl<F: FnMut(&[u8])> Handler for Counter<F> {
+    fn on_data(&mut self, _parser: &mut Parser, data: &[u8]) { (self.on_body)(data); }
+  }
+
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ALL;
+
+  let mut total = 0;
+  let mut counter = Counter {
+    on_body: |data: &[u8]| total += data.len(),
+  };
+
+  parser.parse_with(
+    b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n",
+    &mut counter,
+  );
+
+  assert_eq!(total, 5);
+}
@@ -0,0 +1,290 @@
+mod helpers;
+
+use milo_parser::head::{Head, HeaderRef, Status};
+use milo_parser::{
+  Errors, LENIENT_OBS_FOLD, MESSAGE_TYPE_REQUEST, Methods, PROTOCOL_ICE, PROTOCOL_RTSP, ParserConfig, States,
+  parse_head, parse_head_with_config,
+};
+
+use crate::helpers::http;
+
+#[test]
+fn head_request() {
+  let input = http(
+    r#"
+      \r\n
+      POST /path?query=1 HTTP/1.1\r\n
+      Host: localhost\r\n
+      Content-Length:   3  \r\n
+      X-Empty:\r\n
+      \r\n
+      abc
+    "#,
+  );
+
+  let mut headers = [HeaderRef::EMPTY; 4];
+  let Status::Complete(head) = parse_head(input.as_bytes(), &mut headers).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert!(head.is_request);
+  assert_eq!(head.method, Methods::POST);
+  assert_eq!(head.url, b"/path?query=1");
+  assert_eq!(head.version, "1.1");
+  assert_eq!(
+    headers[..head.header_count],
+    [
+      HeaderRef {
+        name: "Host",
+        value: b"localhost"
+      },
+      HeaderRef {
+        name: "Content-Length",
+        value: b"3"
+      },
+      HeaderRef {
+        name: "X-Empty",
+        value: b""
+      },
+    ]
+  );
+  assert_eq!(&input[head.length..], "abc");
+}
+
+#[test]
+fn head_response() {
+  let input = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let head = parse_head(input, &mut headers).unwrap();
+
+  assert_eq!(
+    head,
+    Status::Complete(Head {
+      is_request: false,
+      method: Methods::OTHER,
+      url: b"",
+      version: "1.1",
+      status: 404,
+      reason: b"Not Found",
+      header_count: 1,
+      length: input.len(),
+    })
+  );
+  assert_eq!(
+    headers[0],
+    HeaderRef {
+      name: "Content-Length",
+      value: b"0"
+    }
+  );
+}
+
+#[test]
+fn head_partial() {
+  let input = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+  for length in 0..input.len() {
+    let mut headers = [HeaderRef::EMPTY; 1];
+    assert_eq!(parse_head(&input[..length], &mut headers), Ok(Status::Partial));
+  }
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  assert!(matches!(
+    parse_head(input, &mut headers),
+    Ok(Status::Complete(Head { length: 35, .. }))
+  ));
+}
+
+#[test]
+fn head_errors() {
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head(b"GET / HTTP/1.1\r\nHo st: value\r\n\r\n", &mut headers).unwrap_err();
+
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.position, 16);
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n", &mut headers).unwrap_err();
+
+  assert_eq!(error.code, Errors::TOO_MANY_HEADERS);
+  assert_eq!(error.position, 22);
+  assert_eq!(error.state, States::HEADER);
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head(
+    b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\nContent-Length: 1\r\n\r\n",
+    &mut headers,
+  )
+  .unwrap_err();
+
+  assert_eq!(error.code, Errors::INVALID_CONTENT_LENGTH);
+}
+
+#[test]
+fn head_protocols() {
+  let input = b"DESCRIBE rtsp://example.com/media RTSP/1.0\r\nCSeq: 1\r\n\r\n";
+
+  let config = ParserConfig {
+    protocol: PROTOCOL_RTSP,
+    ..ParserConfig::default()
+  };
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let Status::Complete(head) = parse_head_with_config(input, &mut headers, &config).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert_eq!(head.method, Methods::DESCRIBE);
+  assert_eq!(head.url, b"rtsp://example.com/media");
+  assert_eq!(head.version, "1.0");
+  assert_eq!(
+    headers[..head.header_count],
+    [HeaderRef {
+      name: "CSeq",
+      value: b"1"
+    }]
+  );
+
+  let input = b"ICE/1.0 200 Connection Established\r\n\r\n";
+
+  let config = ParserConfig {
+    protocol: PROTOCOL_ICE,
+    ..ParserConfig::default()
+  };
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let Status::Complete(head) = parse_head_with_config(input, &mut headers, &config).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert_eq!(head.status, 200);
+  assert_eq!(head.version, "1.0");
+  assert_eq!(head.reason, b"Connection Established");
+  assert_eq!(head.length, input.len());
+}
+
+#[test]
+fn head_many_headers() {
+  let mut input = String::from("GET / HTTP/1.1\r\n");
+  for i in 0..100 {
+    input.push_str(&format!("X-Header-{i}: {i}\r\n"));
+  }
+  input.push_str("\r\n");
+
+  let mut headers = [HeaderRef::EMPTY; 100];
+  let Status::Complete(head) = parse_head(input.as_bytes(), &mut headers).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert_eq!(head.header_count, 100);
+  assert_eq!(
+    headers[99],
+    HeaderRef {
+      name: "X-Header-99",
+      value: b"99"
+    }
+  );
+  assert_eq!(head.length, input.len());
+
+  let mut headers = [HeaderRef::EMPTY; 99];
+  let error = parse_head(input.as_bytes(), &mut headers).unwrap_err();
+
+  assert_eq!(error.code, Errors::TOO_MANY_HEADERS);
+  assert_eq!(error.position, input.len() - 19);
+}
+
+#[test]
+fn head_reuse_headers() {
+  let first = b"GET /first HTTP/1.1\r\nHost: first\r\n\r\n";
+  let second = b"GET /second HTTP/1.1\r\nHost: second\r\n\r\n";
+
+  // The head only borrows the data, so the headers can be reused while it is
+  // alive
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let Status::Complete(first_head) = parse_head(first, &mut headers).unwrap() else {
+    panic!("Expected a complete head");
+  };
+  let first_host = headers[0];
+
+  let Status::Complete(second_head) = parse_head(second, &mut headers).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert_eq!(first_head.url, b"/first");
+  assert_eq!(first_host.value, b"first");
+  assert_eq!(second_head.url, b"/second");
+  assert_eq!(headers[0].value, b"second");
+}
+
+#[test]
+fn head_config() {
+  let input = b"GET / HTTP/1.1\r\nX-Folded: a\r\n  b\r\n\r\n";
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head(input, &mut headers).unwrap_err();
+
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.position, 29);
+
+  let config = ParserConfig {
+    lenient_flags: LENIENT_OBS_FOLD,
+    ..ParserConfig::default()
+  };
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let Status::Complete(head) = parse_head_with_config(input, &mut headers, &config).unwrap() else {
+    panic!("Expected a complete head");
+  };
+
+  assert_eq!(head.length, input.len());
+  assert_eq!(headers[0].value, b"a\r\n  b");
+
+  let config = ParserConfig {
+    message_type: MESSAGE_TYPE_REQUEST,
+    ..ParserConfig::default()
+  };
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head_with_config(b"HTTP/1.1 200 OK\r\n\r\n", &mut headers, &config).unwrap_err();
+
+  assert_eq!(error.state, States::REQUEST_LINE);
+
+  let config = ParserConfig {
+    max_header_length: 16,
+    ..ParserConfig::default()
+  };
+
+  let mut headers = [HeaderRef::EMPTY; 1];
+  let error = parse_head_with_config(b"GET / HTTP/1.1\r\nX-Long: 0123456789", &mut headers, &config).unwrap_err();
+
+  assert_eq!(error.description, "Header line too long");
+}
+
+#[test]
+fn head_framing() {
+  let cases: [(&[u8], Errors); 5] = [
+    (
+      b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 1\r\n\r\n",
+      Errors::UNEXPECTED_CONTENT_LENGTH,
+    ),
+    (
+      b"POST / HTTP/1.1\r\nContent-Length: 1\r\nTransfer-Encoding: chunked\r\n\r\n",
+      Errors::UNEXPECTED_TRANSFER_ENCODING,
+    ),
+    (
+      b"POST / HTTP/1.1\r\nContent-Length: 1a\r\n\r\n",
+      Errors::INVALID_CONTENT_LENGTH,
+    ),
+    (
+      b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n",
+      Errors::UNEXPECTED_CONTENT_LENGTH,
+    ),
+    (b"GET / HTTP/2.0\r\n\r\n", Errors::UNSUPPORTED_HTTP_VERSION),
+  ];
+
+  for (input, code) in cases {
+    let mut headers = [HeaderRef::EMPTY; 2];
+    assert_eq!(parse_head(input, &mut headers).unwrap_err().code, code);
+  }
+}
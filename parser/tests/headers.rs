@@ -0,0 +1,158 @@
+mod helpers;
+
+use std::cell::RefCell;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_HEADER_NAME, EVENT_HEADER_NAME, Event, HEADER_CONTENT_TYPE, HEADER_HOST, HEADER_OTHER,
+  HEADER_WWW_AUTHENTICATE, HEADER_X_REQUEST_ID, Handler, Headers, Parser,
+};
+
+use crate::helpers::http;
+
+fn request() -> String {
+  http(
+    r#"
+      GET / HTTP/1.1\r\n
+      host: localhost\r\n
+      CONTENT-TYPE: text/plain\r\n
+      X-Custom: value\r\n
+      WWW-Authenticate: Basic\r\n
+      \r\n
+    "#,
+  )
+}
+
+#[test]
+fn headers_registry() {
+  assert_eq!(HEADER_OTHER, 0);
+  assert_eq!(Headers::try_from(HEADER_CONTENT_TYPE), Ok(Headers::CONTENT_TYPE));
+  assert_eq!(Headers::CONTENT_TYPE.as_str(), "Content-Type");
+  assert_eq!(Headers::X_REQUEST_ID as u8, HEADER_X_REQUEST_ID);
+  assert_eq!(Headers::try_from(255), Err(()));
+}
+
+#[test]
+fn headers_parse_slice_carries_known_header() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HEADER_NAME;
+
+  let request = request();
+  let events: Vec<_> = parser
+    .parse_slice(request.as_bytes())
+    .map(|event| {
+      match event {
+        Event::HeaderName { data, header, .. } => (String::from_utf8_lossy(data).into_owned(), header),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect();
+
+  assert_eq!(
+    events,
+    [
+      ("host".into(), Headers::HOST),
+      ("CONTENT-TYPE".into(), Headers::CONTENT_TYPE),
+      ("X-Custom".into(), Headers::OTHER),
+      ("WWW-Authenticate".into(), Headers::WWW_AUTHENTICATE),
+    ]
+  );
+
+  // Header name records have a trailing byte with the known header
+  let record = unsafe { std::slice::from_raw_parts(parser.events, 20) };
+  assert_eq!(record[0], EVENT_HEADER_NAME);
+  assert_eq!(record[9], HEADER_HOST);
+  assert_eq!(record[10], EVENT_HEADER_NAME);
+}
+
+#[test]
+fn headers_framing_headers_are_known() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HEADER_NAME;
+
+  let request = http(
+    r#"
+      POST / HTTP/1.1\r\n
+      content-length: 0\r\n
+      Connection: keep-alive\r\n
+      TRAILER: x-checksum\r\n
+      HTTP2-Settings: AAMAAABkAAQCAAAA\r\n
+      Transfer-Encodings: none\r\n
+      \r\n
+    "#,
+  );
+
+  let headers: Vec<_> = parser
+    .parse_slice(request.as_bytes())
+    .filter_map(|event| {
+      match event {
+        Event::HeaderName { header, .. } => Some(header),
+        _ => None,
+      }
+    })
+    .collect();
+
+  assert_eq!(
+    headers,
+    [
+      Headers::CONTENT_LENGTH,
+      Headers::CONNECTION,
+      Headers::TRAILER,
+      Headers::HTTP2_SETTINGS,
+      Headers::OTHER
+    ]
+  );
+}
+
+thread_local! {
+  static RECEIVED: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_header_name(parser: &mut Parser, _at: usize, _len: usize) {
+  RECEIVED.with(|received| received.borrow_mut().push(parser.known_header));
+}
+
+#[test]
+fn headers_callbacks_receive_known_header() {
+  let mut parser = Parser::new();
+  parser.callbacks.on_header_name = on_header_name;
+  parser.active_callbacks = EVENT_ACTIVE_ON_HEADER_NAME;
+
+  let request = request();
+  parser.parse(request.as_ptr(), request.len());
+
+  assert_eq!(
+    RECEIVED.take(),
+    [HEADER_HOST, HEADER_CONTENT_TYPE, HEADER_OTHER, HEADER_WWW_AUTHENTICATE]
+  );
+}
+
+#[derive(Default)]
+struct Recorder {
+  headers: Vec<Headers>,
+}
+
+impl Handler for Recorder {
+  fn on_header_name(&mut self, parser: &mut Parser, _data: &[u8], header: Headers) {
+    assert_eq!(header as u8, parser.known_header);
+    self.headers.push(header);
+  }
+}
+
+#[test]
+fn headers_handler_receives_known_header() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HEADER_NAME;
+
+  let mut recorder = Recorder::default();
+  parser.parse_with(request().as_bytes(), &mut recorder);
+
+  assert_eq!(
+    recorder.headers,
+    [
+      Headers::HOST,
+      Headers::CONTENT_TYPE,
+      Headers::OTHER,
+      Headers::WWW_AUTHENTICATE
+    ]
+  );
+}
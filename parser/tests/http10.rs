@@ -0,0 +1,132 @@
+mod helpers;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_HEADERS, EVENT_ACTIVE_ON_VERSION, Errors, Event, Events, Parser, ParserConfig, STATE_ERROR,
+  STATE_FINISH, STATE_START,
+};
+
+use crate::helpers::http;
+
+fn http10_parser() -> Parser {
+  let mut parser = Parser::new();
+  parser.allow_http10 = true;
+  parser.active_events = EVENT_ACTIVE_ON_HEADERS | EVENT_ACTIVE_ON_VERSION;
+  parser
+}
+
+// Returns the reported versions and the keep-alive flags of the messages
+fn parse(parser: &mut Parser, input: &str) -> Vec<(String, u8, bool)> {
+  let mut versions = Vec::new();
+  let mut messages = Vec::new();
+
+  for event in parser.parse_slice(input.as_bytes()) {
+    match event {
+      Event::Range {
+        event: Events::VERSION,
+        data,
+        ..
+      } => versions.push(String::from_utf8_lossy(data).into_owned()),
+      Event::Headers(metadata) => {
+        messages.push((
+          versions.pop().unwrap(),
+          metadata.version_minor,
+          metadata.should_keep_alive,
+        ))
+      }
+      _ => (),
+    }
+  }
+
+  messages
+}
+
+#[test]
+fn http10_rejected_by_default() {
+  for input in ["GET / HTTP/1.0\r\n\r\n", "HTTP/1.0 200 OK\r\n\r\n"] {
+    let mut parser = Parser::new();
+    parser.parse(input.as_ptr(), input.len());
+
+    assert_eq!(parser.state, STATE_ERROR);
+  }
+}
+
+#[test]
+fn http10_closes_by_default() {
+  let mut parser = http10_parser();
+
+  assert_eq!(
+    parse(&mut parser, "GET / HTTP/1.0\r\nHost: localhost\r\n\r\n"),
+    [("1.0".into(), 0, false)]
+  );
+  assert_eq!(parser.state, STATE_FINISH);
+  assert!(parser.has_connection_close);
+
+  // Data after the message is rejected
+  let mut parser = http10_parser();
+  parse(&mut parser, "GET / HTTP/1.0\r\n\r\nGET / HTTP/1.0\r\n\r\n");
+
+  assert_eq!(parser.error().unwrap().code, Errors::UNEXPECTED_CHARACTER);
+}
+
+#[test]
+fn http10_keep_alive_persists() {
+  let mut parser = http10_parser();
+  let input = http(
+    r#"
+      GET /first HTTP/1.0\r\n
+      Connection: keep-alive\r\n
+      \r\n
+      GET /second HTTP/1.1\r\n
+      \r\n
+    "#,
+  );
+
+  assert_eq!(
+    parse(&mut parser, &input),
+    [("1.0".into(), 0, true), ("1.1".into(), 1, true)]
+  );
+  assert_eq!(parser.state, STATE_START);
+}
+
+#[test]
+fn http10_response_body_until_eof() {
+  let mut parser = http10_parser();
+  let input = "HTTP/1.0 200 OK\r\nConnection: Keep-Alive, foo\r\nContent-Length: 3\r\n\r\nabc";
+
+  assert_eq!(parse(&mut parser, input), [("1.0".into(), 0, true)]);
+  assert_eq!(parser.state, STATE_START);
+
+  let mut parser = http10_parser();
+  parse(&mut parser, "HTTP/1.0 200 OK\r\n\r\nuntil the end");
+  parser.finish();
+
+  assert_eq!(parser.state, STATE_FINISH);
+}
+
+#[test]
+fn http10_rejects_transfer_encoding() {
+  let mut parser = http10_parser();
+  parse(
+    &mut parser,
+    "POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+  );
+
+  assert_eq!(parser.error().unwrap().code, Errors::UNEXPECTED_TRANSFER_ENCODING);
+}
+
+#[test]
+fn http10_config() {
+  let config = ParserConfig::builder().allow_http10(true).build().unwrap();
+  let mut parser = Parser::with_config(config).unwrap();
+  parser.allow_http10 = false;
+
+  // The configuration is restored on reset
+  parser.reset(false);
+  assert!(parser.allow_http10);
+
+  let input = "HTTP/1.0 204 No Content\r\n\r\n";
+  parser.parse(input.as_ptr(), input.len());
+
+  assert_eq!(parser.state, STATE_FINISH);
+  assert_eq!(parser.version_minor, 0);
+}
@@ -0,0 +1,135 @@
+mod helpers;
+
+use bytes::Bytes;
+use http::{Method, StatusCode, Version};
+use milo_parser::{
+  Assembled, Header, HttpConversionError, Message, MessageAssembler, Methods, Request, Response, to_bytes,
+  to_header_map, to_http_method, to_http_status, to_http_version,
+};
+
+use crate::helpers::http;
+
+fn assemble(input: &str) -> Message {
+  let mut assembler = MessageAssembler::new();
+  assembler.feed(input.as_bytes()).unwrap();
+
+  match assembler.next_item() {
+    Some(Assembled::Message(message)) => message,
+    item => panic!("Unexpected item {item:?}"),
+  }
+}
+
+#[test]
+fn interop_request() {
+  let Message::Request(request) = assemble(&http(
+    r#"
+      POST /path?query=1 HTTP/1.1\r\n
+      Host: localhost\r\n
+      Accept: text/plain\r\n
+      Accept: text/html\r\n
+      Content-Length: 3\r\n
+      \r\n
+      abc
+    "#,
+  )) else {
+    panic!("Expected a request");
+  };
+
+  let request = request.into_http().unwrap();
+
+  assert_eq!(request.method(), Method::POST);
+  assert_eq!(request.uri().path(), "/path");
+  assert_eq!(request.uri().query(), Some("query=1"));
+  assert_eq!(request.version(), Version::HTTP_11);
+  assert_eq!(request.headers()["host"], "localhost");
+  assert_eq!(request.headers().get_all("accept").iter().count(), 2);
+  assert_eq!(request.body(), &Bytes::from_static(b"abc"));
+
+  // Unknown methods are converted using the received name
+  let Message::Request(request) = assemble("PURGE /cache HTTP/1.1\r\n\r\n") else {
+    panic!("Expected a request");
+  };
+
+  assert_eq!(
+    request.to_http_parts().unwrap().method,
+    Method::from_bytes(b"PURGE").unwrap()
+  );
+}
+
+#[test]
+fn interop_response() {
+  let Message::Response(response) = assemble(&http(
+    r#"
+      HTTP/1.1 404 Not Found\r\n
+      Content-Length: 0\r\n
+      \r\n
+    "#,
+  )) else {
+    panic!("Expected a response");
+  };
+
+  let parts = response.to_http_parts().unwrap();
+
+  assert_eq!(parts.status, StatusCode::NOT_FOUND);
+  assert_eq!(parts.version, Version::HTTP_11);
+  assert_eq!(parts.headers["content-length"], "0");
+}
+
+#[test]
+fn interop_helpers() {
+  assert_eq!(to_http_method(Methods::GET), Ok(Method::GET));
+  assert_eq!(to_http_method(Methods::PRI).unwrap().as_str(), "PRI");
+  assert_eq!(to_http_status(204), Ok(StatusCode::NO_CONTENT));
+  assert_eq!(to_http_version("1.1"), Ok(Version::HTTP_11));
+  assert_eq!(to_bytes(b"data"), Bytes::from_static(b"data"));
+
+  let map = to_header_map([(&b"X-Name"[..], &b"value"[..])]).unwrap();
+  assert_eq!(map["x-name"], "value");
+}
+
+#[test]
+fn interop_errors() {
+  assert_eq!(
+    to_http_method(Methods::OTHER),
+    Err(HttpConversionError::InvalidMethod(Methods::OTHER))
+  );
+  assert_eq!(to_http_status(1000), Err(HttpConversionError::InvalidStatus(1000)));
+  assert_eq!(
+    to_http_version("2.0"),
+    Err(HttpConversionError::InvalidVersion("2.0".into()))
+  );
+  assert_eq!(
+    to_header_map([(&b"X-Name"[..], &b"a\x7fb"[..])]),
+    Err(HttpConversionError::InvalidHeaderValue("X-Name".into()))
+  );
+
+  let request = Request {
+    method: Methods::GET,
+    method_name: "GET".into(),
+    url: "/a b".into(),
+    version: "1.1".into(),
+    headers: vec![],
+    body: vec![],
+    trailers: vec![],
+    keep_alive: true,
+  };
+
+  let error = request.to_http_parts().unwrap_err();
+  assert_eq!(error, HttpConversionError::InvalidUri("/a b".into()));
+  assert_eq!(error.to_string(), "Invalid URI /a b");
+
+  let response = Response {
+    status: 200,
+    version: "1.1".into(),
+    headers: vec![Header {
+      name: "Bad Name".into(),
+      value: b"value".to_vec(),
+    }],
+    ..Response::default()
+  };
+
+  assert_eq!(
+    response.into_http().unwrap_err(),
+    HttpConversionError::InvalidHeaderName("Bad Name".into())
+  );
+}
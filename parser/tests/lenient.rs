@@ -0,0 +1,278 @@
+mod helpers;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_DATA, EVENT_ACTIVE_ON_HEADER_NAME, EVENT_ACTIVE_ON_HEADER_VALUE, EVENT_ACTIVE_ON_MESSAGE_COMPLETE,
+  EVENT_ACTIVE_ON_TRAILER_NAME, EVENT_ACTIVE_ON_TRAILER_VALUE, Errors, Event, Events, LENIENT_OBS_FOLD,
+  LENIENT_OPTIONAL_CR_BEFORE_LF, LENIENT_OPTIONAL_CRLF_AFTER_CHUNK, LENIENT_OPTIONAL_LF_AFTER_CR,
+  LENIENT_SPACES_AFTER_CHUNK_SIZE, LENIENT_SPACES_BEFORE_COLON, Parser, ParserConfig, STATE_ERROR, STATE_FINISH,
+};
+
+use crate::helpers::llhttp::{load_test, parse_input};
+
+const EVENTS: u64 = EVENT_ACTIVE_ON_HEADER_NAME
+  | EVENT_ACTIVE_ON_HEADER_VALUE
+  | EVENT_ACTIVE_ON_TRAILER_NAME
+  | EVENT_ACTIVE_ON_TRAILER_VALUE
+  | EVENT_ACTIVE_ON_DATA
+  | EVENT_ACTIVE_ON_MESSAGE_COMPLETE;
+
+#[derive(Debug, Default)]
+struct Outcome {
+  fields: Vec<(String, String)>,
+  body: String,
+  completed: usize,
+}
+
+fn fixture(section: &str, name: &str) -> String {
+  let path = format!("./tests/fixtures/llhttp/{}/{}.yml", section, name);
+  parse_input(&load_test(section, &path).input)
+}
+
+fn lenient_parser(flags: u64) -> Parser {
+  let mut parser = Parser::new();
+  parser.lenient_flags = flags;
+  parser.active_events = EVENTS;
+  parser
+}
+
+// Returns the header and trailer fields, the body and the number of completed
+// messages
+fn run(parser: &mut Parser, input: &str) -> Outcome {
+  let mut outcome = Outcome::default();
+
+  for event in parser.parse_slice(input.as_bytes()) {
+    match event {
+      Event::HeaderName { data, .. }
+      | Event::Range {
+        event: Events::TRAILER_NAME,
+        data,
+        ..
+      } => {
+        outcome
+          .fields
+          .push((String::from_utf8_lossy(data).into_owned(), String::new()))
+      }
+      Event::Range {
+        event: Events::HEADER_VALUE | Events::TRAILER_VALUE,
+        data,
+        ..
+      } => outcome.fields.last_mut().unwrap().1 = String::from_utf8_lossy(data).into_owned(),
+      Event::Range {
+        event: Events::DATA,
+        data,
+        ..
+      } => outcome.body.push_str(&String::from_utf8_lossy(data)),
+      Event::Range {
+        event: Events::MESSAGE_COMPLETE,
+        ..
+      } => outcome.completed += 1,
+      _ => (),
+    }
+  }
+
+  outcome
+}
+
+fn field(name: &str, value: &str) -> (String, String) { (name.into(), value.into()) }
+
+#[test]
+fn lenient_disabled_by_default() {
+  for (section, name) in [
+    ("requests", "x-ssl-nonsense"),
+    ("requests", "invalid-space-after-start-line-only-lfs-present-lenient"),
+    ("requests", "no-lf-after-cr-lenient"),
+    (
+      "requests",
+      "invalid-obs-fold-after-chunked-value-chunk-data-not-terminated-by-crlf-lenient",
+    ),
+    ("requests", "space-after-chunk-header-lenient"),
+    (
+      "requests",
+      "invalid-whitespace-token-with-content-length-header-field-lenient",
+    ),
+    ("responses", "no-carriage-ret-lenient"),
+  ] {
+    let input = fixture(section, name);
+    let mut parser = Parser::new();
+    let consumed = parser.parse(input.as_ptr(), input.len());
+
+    // The input is either rejected or not entirely recognized
+    assert!(parser.state == STATE_ERROR || consumed < input.len(), "{}", name);
+  }
+}
+
+#[test]
+fn lenient_obs_fold() {
+  let mut parser = lenient_parser(LENIENT_OBS_FOLD);
+  let outcome = run(&mut parser, &fixture("requests", "x-ssl-nonsense"));
+  let (name, value) = &outcome.fields[0];
+
+  // Folds are reported as part of the value
+  assert_eq!(name, "X-SSL-Nonsense");
+  assert!(
+    value
+      .trim_start()
+      .starts_with("-----BEGIN CERTIFICATE-----\r\n\tMIIFbTCC")
+  );
+  assert!(value.ends_with("RA==\r\n\t-----END CERTIFICATE-----"));
+  assert_eq!(outcome.completed, 1);
+
+  // A line starting with whitespace never starts a new field
+  let mut parser = lenient_parser(LENIENT_OBS_FOLD);
+  let outcome = run(
+    &mut parser,
+    &fixture(
+      "requests",
+      "invalid-space-after-start-line-spaces-before-headers-lenient",
+    ),
+  );
+
+  assert_eq!(
+    outcome.fields,
+    [
+      field("Host", "localhost"),
+      field("Foo", "bar\r\n Content-Length: 38"),
+      field("Host", "localhost")
+    ]
+  );
+  assert_eq!(outcome.completed, 2);
+
+  // Headers interpreted by the parser cannot be folded
+  let mut parser = lenient_parser(LENIENT_OBS_FOLD);
+  run(
+    &mut parser,
+    &fixture("requests", "invalid-obs-fold-after-chunked-value"),
+  );
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.description, "Unexpected obs-fold in Transfer-Encoding header");
+}
+
+#[test]
+fn lenient_optional_cr_before_lf() {
+  let mut parser = lenient_parser(LENIENT_OPTIONAL_CR_BEFORE_LF);
+  let outcome = run(
+    &mut parser,
+    &fixture("requests", "invalid-space-after-start-line-only-lfs-present-lenient"),
+  );
+
+  assert_eq!(
+    outcome.fields,
+    [
+      field("Transfer-Encoding", "chunked"),
+      field("Trailer", "Baz"),
+      field("Foo", "abc"),
+      field("Bar", "def")
+    ]
+  );
+  assert_eq!(outcome.body, "ABCD");
+  assert_eq!(outcome.completed, 1);
+
+  let mut parser = lenient_parser(LENIENT_OPTIONAL_CR_BEFORE_LF);
+  let outcome = run(&mut parser, &fixture("responses", "no-carriage-ret-lenient"));
+  parser.finish();
+
+  assert_eq!(outcome.body, "these headers are from http://news.ycombinator.com/");
+  assert_eq!(parser.state, STATE_FINISH);
+}
+
+#[test]
+fn lenient_optional_lf_after_cr() {
+  let mut parser = lenient_parser(LENIENT_OPTIONAL_LF_AFTER_CR);
+  let outcome = run(&mut parser, &fixture("requests", "no-lf-after-cr-lenient"));
+
+  assert_eq!(outcome.fields, [field("Line", "1")]);
+  assert_eq!(run(&mut parser, "\r\n").completed, 1);
+}
+
+#[test]
+fn lenient_optional_crlf_after_chunk() {
+  let mut parser = lenient_parser(LENIENT_OPTIONAL_CRLF_AFTER_CHUNK);
+  let outcome = run(
+    &mut parser,
+    &fixture(
+      "requests",
+      "invalid-obs-fold-after-chunked-value-chunk-data-not-terminated-by-crlf-lenient",
+    ),
+  );
+
+  assert_eq!(outcome.body, "ABCDE");
+  assert_eq!(run(&mut parser, "\r\n").completed, 1);
+  assert_eq!(parser.state, STATE_FINISH);
+}
+
+#[test]
+fn lenient_spaces_after_chunk_size() {
+  let config = ParserConfig::builder()
+    .lenient_flags(LENIENT_SPACES_AFTER_CHUNK_SIZE)
+    .build()
+    .unwrap();
+  let mut parser = Parser::with_config(config).unwrap();
+  parser.lenient_flags = 0;
+
+  // The configuration is restored on reset
+  parser.reset(false);
+  assert_eq!(parser.lenient_flags, LENIENT_SPACES_AFTER_CHUNK_SIZE);
+
+  parser.active_events = EVENTS;
+  let outcome = run(&mut parser, &fixture("requests", "space-after-chunk-header-lenient"));
+
+  assert_eq!(outcome.body, "0123456789");
+  assert_eq!(outcome.completed, 1);
+}
+
+#[test]
+fn lenient_spaces_before_colon() {
+  let mut parser = lenient_parser(LENIENT_SPACES_BEFORE_COLON);
+  let outcome = run(
+    &mut parser,
+    "POST / HTTP/1.1\r\nHost \t: localhost\r\nContent-Length: 2\r\n\r\nok",
+  );
+
+  assert_eq!(
+    outcome.fields,
+    [field("Host", "localhost"), field("Content-Length", "2")]
+  );
+  assert_eq!(outcome.body, "ok");
+  assert_eq!(outcome.completed, 1);
+}
+
+#[test]
+fn lenient_spaces_before_colon_framing_headers() {
+  // Framing headers are rejected even when the flag is enabled
+  for (name, section) in [
+    (
+      "Content-Length",
+      "invalid-whitespace-token-with-content-length-header-field-lenient",
+    ),
+    (
+      "Connection",
+      "parsing-multiple-tokens-invalid-whitespace-token-with-connection-header-field-lenient",
+    ),
+  ] {
+    let mut parser = lenient_parser(LENIENT_SPACES_BEFORE_COLON);
+    run(&mut parser, &fixture("requests", section));
+
+    let error = parser.error().unwrap();
+    assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER, "{}", name);
+    assert_eq!(
+      error.description,
+      format!("Unexpected whitespace before colon in {name} header"),
+      "{}",
+      name
+    );
+  }
+
+  for name in ["Transfer-Encoding", "Upgrade"] {
+    let mut parser = lenient_parser(LENIENT_SPACES_BEFORE_COLON);
+    run(&mut parser, &format!("POST / HTTP/1.1\r\n{name} : value\r\n\r\n"));
+
+    assert_eq!(
+      parser.error().unwrap().description,
+      format!("Unexpected whitespace before colon in {name} header"),
+      "{}",
+      name
+    );
+  }
+}
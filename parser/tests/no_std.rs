@@ -0,0 +1,47 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+const EMBEDDED_TARGET: &str = "thumbv7em-none-eabi";
+
+fn has_target(target: &str) -> bool {
+  let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+
+  Command::new(rustc)
+    .args(["--print", "sysroot"])
+    .output()
+    .ok()
+    .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    .is_some_and(|sysroot| Path::new(&sysroot).join("lib/rustlib").join(target).exists())
+}
+
+#[test]
+fn no_std_builds_without_std_feature() {
+  let manifest_dir = env!("CARGO_MANIFEST_DIR");
+  let mut command = Command::new(env!("CARGO"));
+
+  // Only build the rlib, as the C libraries require a panic handler and a global
+  // allocator on no_std targets
+  command.current_dir(manifest_dir).args([
+    "rustc",
+    "--lib",
+    "--crate-type",
+    "rlib",
+    "--no-default-features",
+    "--target-dir",
+    &format!("{manifest_dir}/target/no_std"),
+  ]);
+
+  // Building for the host would link std implicitly, so the embedded target is
+  // required for this test to be meaningful
+  assert!(
+    has_target(EMBEDDED_TARGET),
+    "The {EMBEDDED_TARGET} target is not installed, add it via \"rustup target add {EMBEDDED_TARGET}\""
+  );
+
+  command.args(["--target", EMBEDDED_TARGET]);
+
+  let output = command.output().unwrap();
+
+  assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -0,0 +1,179 @@
+mod helpers;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_DATA, EVENT_ACTIVE_ON_HEADERS, EVENT_ACTIVE_ON_METHOD, EVENT_ACTIVE_ON_PROTOCOL,
+  EVENT_ACTIVE_ON_VERSION, Errors, Event, Events, METHOD_OTHER, METHOD_SETUP, PROTOCOL_HTTP, PROTOCOL_ICE,
+  PROTOCOL_RTSP, Parser, ParserConfig, STATE_ERROR, milo_set_protocol,
+};
+
+use crate::helpers::http;
+
+const EVENTS: u64 = EVENT_ACTIVE_ON_METHOD
+  | EVENT_ACTIVE_ON_PROTOCOL
+  | EVENT_ACTIVE_ON_VERSION
+  | EVENT_ACTIVE_ON_HEADERS
+  | EVENT_ACTIVE_ON_DATA;
+
+fn protocol_parser(protocol: u8) -> Parser {
+  let mut parser = Parser::new();
+  parser.protocol = protocol;
+  parser.active_events = EVENTS;
+  parser
+}
+
+// Returns the reported ranges and the methods or statuses of the messages
+fn parse(parser: &mut Parser, input: &str) -> (Vec<String>, Vec<u16>) {
+  let mut ranges = Vec::new();
+  let mut methods = Vec::new();
+
+  for event in parser.parse_slice(input.as_bytes()) {
+    match event {
+      Event::Range {
+        event: Events::METHOD | Events::PROTOCOL | Events::VERSION | Events::DATA,
+        data,
+        ..
+      } => ranges.push(String::from_utf8_lossy(data).into_owned()),
+      Event::Headers(metadata) => methods.push(metadata.status_or_method),
+      _ => (),
+    }
+  }
+
+  (ranges, methods)
+}
+
+#[test]
+fn protocols_http_by_default() {
+  for input in [
+    "SETUP rtsp://localhost/media RTSP/1.0\r\n\r\n",
+    "RTSP/1.0 200 OK\r\n\r\n",
+    "ICE/1.0 200 OK\r\n\r\n",
+  ] {
+    let mut parser = Parser::new();
+    parser.parse(input.as_ptr(), input.len());
+
+    assert_eq!(parser.state, STATE_ERROR, "{}", input);
+  }
+
+  // RTSP methods are not recognized in HTTP messages
+  let mut parser = protocol_parser(PROTOCOL_HTTP);
+  let (_, methods) = parse(&mut parser, "SETUP / HTTP/1.1\r\n\r\n");
+
+  assert_eq!(methods, [METHOD_OTHER as u16]);
+}
+
+#[test]
+fn protocols_rtsp() {
+  let config = ParserConfig::builder().protocol(PROTOCOL_RTSP).build().unwrap();
+  let mut parser = Parser::with_config(config).unwrap();
+  parser.active_events = EVENTS;
+
+  let input = http(
+    r#"
+      SETUP rtsp://localhost/media.mp4/track1 RTSP/1.0\r\n
+      CSeq: 2\r\n
+      Transport: RTP/AVP;unicast;client_port=8000-8001\r\n
+      \r\n
+      RTSP/1.0 200 OK\r\n
+      CSeq: 2\r\n
+      Session: 12345678\r\n
+      \r\n
+      RTSP/1.0 200 OK\r\n
+      CSeq: 3\r\n
+      Content-Length: 4\r\n
+      \r\n
+      abcd
+    "#,
+  );
+
+  // The parser autodetects the message type of each message
+  let (ranges, methods) = parse(&mut parser, &input);
+
+  assert_eq!(ranges, ["SETUP", "RTSP", "1.0", "RTSP", "1.0", "RTSP", "1.0", "abcd"]);
+  assert_eq!(methods, [METHOD_SETUP as u16, 200, 200]);
+
+  // Responses without Content-Length have no body and the connection persists
+  assert_ne!(parser.state, STATE_ERROR);
+  assert!(!parser.has_connection_close);
+  assert_eq!(parser.version_minor, 0);
+
+  // The configuration is restored on reset
+  parser.protocol = PROTOCOL_HTTP;
+  parser.reset(false);
+  assert_eq!(parser.protocol, PROTOCOL_RTSP);
+}
+
+#[test]
+fn protocols_rtsp_errors() {
+  for (input, code, description) in [
+    (
+      "SETUP / HTTP/1.1\r\n\r\n",
+      Errors::UNEXPECTED_CHARACTER,
+      "Invalid protocol",
+    ),
+    (
+      "SETUP / RTSP/2.0\r\n\r\n",
+      Errors::UNSUPPORTED_HTTP_VERSION,
+      "Unsupported protocol version",
+    ),
+    (
+      "HTTP/1.1 200 OK\r\n\r\n",
+      Errors::UNEXPECTED_CHARACTER,
+      "Expected / after the protocol name",
+    ),
+    (
+      "RTSP/2.0 200 OK\r\n\r\n",
+      Errors::UNSUPPORTED_HTTP_VERSION,
+      "Unsupported protocol version",
+    ),
+    (
+      "PLAY / RTSP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n",
+      Errors::UNEXPECTED_TRANSFER_ENCODING,
+      "Unexpected Transfer-Encoding header in a RTSP or ICE message",
+    ),
+  ] {
+    let mut parser = protocol_parser(PROTOCOL_RTSP);
+    parse(&mut parser, input);
+
+    let error = parser.error().unwrap();
+    assert_eq!(error.code, code, "{}", input);
+    assert_eq!(error.description, description, "{}", input);
+  }
+}
+
+#[test]
+fn protocols_ice() {
+  let mut parser = protocol_parser(PROTOCOL_ICE);
+  let (ranges, _) = parse(&mut parser, "ICE/1.0 200 OK\r\nicy-name: radio\r\n\r\nstream");
+
+  // Responses without Content-Length are read until the connection is closed
+  assert_eq!(ranges, ["ICE", "1.0", "stream"]);
+  assert!(parser.has_connection_close);
+
+  let mut parser = protocol_parser(PROTOCOL_ICE);
+  let (ranges, _) = parse(&mut parser, "SOURCE /mount ICE/1.0\r\n\r\n");
+
+  assert_eq!(ranges, ["SOURCE", "ICE", "1.0"]);
+  assert_ne!(parser.state, STATE_ERROR);
+}
+
+#[test]
+fn protocols_pri_is_a_plain_method() {
+  // The HTTP/2 preface is only recognized when parsing HTTP
+  let mut parser = protocol_parser(PROTOCOL_ICE);
+  let (ranges, _) = parse(&mut parser, "PRI * ICE/1.0\r\n\r\n");
+
+  assert_eq!(ranges, ["PRI", "ICE", "1.0"]);
+  assert_ne!(parser.state, STATE_ERROR);
+  assert_eq!(parser.method, METHOD_OTHER);
+}
+
+#[test]
+fn protocols_set_protocol() {
+  let mut parser = Parser::new();
+
+  assert!(milo_set_protocol(&mut parser, PROTOCOL_RTSP));
+  assert_eq!(parser.protocol, PROTOCOL_RTSP);
+
+  assert!(!milo_set_protocol(&mut parser, PROTOCOL_ICE + 1));
+  assert_eq!(parser.protocol, PROTOCOL_RTSP);
+}
@@ -0,0 +1,155 @@
+mod helpers;
+
+use std::io::{self, Cursor, Read};
+
+use milo_parser::{BodyReader, Errors, Header, ParseError, Parser, STATE_BODY_DECISION, STATE_FINISH};
+
+use crate::helpers::http;
+
+// A source returning a single byte per read
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+  fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+    if self.0.is_empty() || output.is_empty() {
+      return Ok(0);
+    }
+
+    output[0] = self.0[0];
+    self.0 = &self.0[1..];
+    Ok(1)
+  }
+}
+
+// Parses the head of a message, returning the parser and the head length
+fn parse_head(input: &[u8]) -> (Parser, usize) {
+  let mut parser = Parser::new();
+  parser.suspend_after_headers = true;
+
+  let consumed = parser.parse(input.as_ptr(), input.len());
+  assert_eq!(parser.state, STATE_BODY_DECISION);
+
+  (parser, consumed)
+}
+
+fn read_body<R: Read>(reader: &mut BodyReader<R>) -> io::Result<String> {
+  let mut body = String::new();
+  reader.read_to_string(&mut body)?;
+  Ok(body)
+}
+
+#[test]
+fn reader_content_length() {
+  let input = http(
+    r#"
+      POST / HTTP/1.1\r\n
+      Content-Length: 11\r\n
+      \r\n
+      hello world
+      GET / HTTP/1.1\r\n\r\n
+    "#,
+  );
+
+  let (parser, head) = parse_head(input.as_bytes());
+
+  // Part of the body was already read together with the head
+  let mut reader = BodyReader::new(
+    Trickle(&input.as_bytes()[head + 3..]),
+    parser,
+    &input.as_bytes()[head..head + 3],
+  );
+
+  assert_eq!(read_body(&mut reader).unwrap(), "hello world");
+  assert!(reader.is_complete());
+  assert!(reader.trailers().is_empty());
+
+  let (mut source, _, buffered) = reader.into_parts();
+  let mut rest = buffered;
+  source.read_to_end(&mut rest).unwrap();
+
+  assert_eq!(rest, b"GET / HTTP/1.1\r\n\r\n");
+}
+
+#[test]
+fn reader_chunked_with_trailers() {
+  let input = http(
+    r#"
+      HTTP/1.1 200 OK\r\n
+      Transfer-Encoding: chunked\r\n
+      Trailer: x-checksum\r\n
+      \r\n
+      5;ext=1\r\n
+      hello\r\n
+      6\r\n
+      _world\r\n
+      0\r\n
+      x-checksum: abc\r\n
+      \r\n
+      HTTP/1.1 204 No Content\r\n\r\n
+    "#,
+  );
+
+  let (parser, head) = parse_head(input.as_bytes());
+  let mut reader = BodyReader::new(Cursor::new(&input.as_bytes()[head..]), parser, &[]);
+
+  assert_eq!(read_body(&mut reader).unwrap(), "hello_world");
+  assert_eq!(
+    reader.trailers(),
+    [Header {
+      name: "x-checksum".into(),
+      value: b"abc".to_vec()
+    }]
+  );
+
+  // The following message is never fed to the parser
+  let (_, parser, buffered) = reader.into_parts();
+  assert_eq!(parser.parsed as usize, input.find("HTTP/1.1 204").unwrap());
+  assert_eq!(buffered, b"HTTP/1.1 204 No Content\r\n\r\n");
+}
+
+#[test]
+fn reader_body_delimited_by_eof() {
+  let input = b"HTTP/1.1 200 OK\r\n\r\nuntil the end";
+
+  let (parser, head) = parse_head(input);
+  let mut reader = BodyReader::new(Trickle(&input[head..]), parser, &[]);
+
+  assert_eq!(read_body(&mut reader).unwrap(), "until the end");
+  assert!(reader.is_complete());
+  assert_eq!(reader.parser().state, STATE_FINISH);
+}
+
+#[test]
+fn reader_without_body() {
+  let input = b"GET / HTTP/1.1\r\n\r\n";
+
+  let (parser, head) = parse_head(input);
+  let mut reader = BodyReader::new(Cursor::new(&input[head..]), parser, &[]);
+
+  assert_eq!(read_body(&mut reader).unwrap(), "");
+  assert!(reader.is_complete());
+}
+
+#[test]
+fn reader_errors() {
+  let input = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n";
+
+  let (parser, head) = parse_head(input);
+  let mut reader = BodyReader::new(Cursor::new(&input[head..]), parser, &[]);
+  let error = read_body(&mut reader).unwrap_err();
+
+  assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+  assert_eq!(
+    error.get_ref().unwrap().downcast_ref::<ParseError>().unwrap().code,
+    Errors::UNEXPECTED_CHARACTER
+  );
+
+  let input = b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort";
+
+  let (parser, head) = parse_head(input);
+  let mut reader = BodyReader::new(Cursor::new(&input[head..]), parser, &[]);
+  let error = read_body(&mut reader).unwrap_err();
+
+  assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+  assert!(!reader.is_complete());
+}
@@ -0,0 +1,164 @@
+mod helpers;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_DATA, Event, Events, MESSAGE_TYPE_REQUEST, Parser, ParserConfig, SNAPSHOT_VERSION, STATE_CHUNK_DATA,
+  STATE_CHUNK_HEADER, STATE_ERROR, STATE_START, SnapshotError,
+};
+
+use crate::helpers::http;
+
+fn body(parser: &mut Parser, input: &[u8]) -> Vec<u8> {
+  parser
+    .parse_slice(input)
+    .filter_map(|event| {
+      match event {
+        Event::Range {
+          event: Events::DATA,
+          data,
+          ..
+        } => Some(data.to_vec()),
+        _ => None,
+      }
+    })
+    .flatten()
+    .collect()
+}
+
+#[test]
+fn snapshot_restore_mid_message() {
+  let config = ParserConfig::builder()
+    .message_type(MESSAGE_TYPE_REQUEST)
+    .manage_unconsumed(true)
+    .build()
+    .unwrap();
+
+  let mut source = Parser::with_config(config).unwrap();
+  source.active_events = EVENT_ACTIVE_ON_DATA;
+
+  let request = http(
+    r#"
+      POST / HTTP/1.1\r\n
+      Transfer-Encoding: chunked\r\n
+      \r\n
+      a\r\n
+      0123456789\r\n
+      5\r\n
+      abcde\r\n
+      0\r\n
+      \r\n
+    "#,
+  );
+
+  // Stop in the middle of a chunk header, which is retained as unconsumed data
+  let split = request.find("89\r\n5").unwrap() + 5;
+  let mut received = body(&mut source, &request.as_bytes()[..split]);
+
+  assert_eq!(source.state, STATE_CHUNK_HEADER);
+  assert_eq!(source.unconsumed_len, 1);
+
+  let snapshot = source.snapshot();
+  assert_eq!(&snapshot[..4], b"MILO");
+  assert_eq!(snapshot[4], SNAPSHOT_VERSION);
+
+  let mut target = Parser::new();
+  target.active_events = EVENT_ACTIVE_ON_DATA;
+  target.restore(&snapshot).unwrap();
+
+  assert_eq!(target.snapshot(), snapshot);
+  assert!(target.has_config);
+  assert_eq!(target.config, config);
+  assert_eq!(target.unconsumed_len, source.unconsumed_len);
+
+  received.extend(body(&mut target, &request.as_bytes()[split..]));
+
+  assert_ne!(target.state, STATE_ERROR);
+  assert_eq!(target.parsed, request.len() as u64);
+  assert_eq!(received, b"0123456789abcde");
+}
+
+#[test]
+fn snapshot_restore_errors() {
+  let mut parser = Parser::new();
+  parser.parse(b"GET / HTTP/1.1\r\n".as_ptr(), 16);
+
+  let snapshot = parser.snapshot();
+  let mut target = Parser::new();
+  let pristine = target.snapshot();
+
+  assert_eq!(target.restore(b"NOPE"), Err(SnapshotError::InvalidMagic));
+  assert_eq!(target.restore(b"MI"), Err(SnapshotError::InvalidMagic));
+
+  let mut invalid = snapshot.clone();
+  invalid[4] = SNAPSHOT_VERSION + 1;
+  assert_eq!(target.restore(&invalid), Err(SnapshotError::UnsupportedVersion));
+
+  assert_eq!(
+    target.restore(&snapshot[..snapshot.len() - 1]),
+    Err(SnapshotError::InvalidLength)
+  );
+
+  let mut invalid = snapshot.clone();
+  invalid.push(0);
+  assert_eq!(target.restore(&invalid), Err(SnapshotError::InvalidLength));
+
+  // Locate the autodetect flag by comparing with a snapshot which only differs
+  // in it
+  let mut other = Parser::new();
+  other.parse(b"GET / HTTP/1.1\r\n".as_ptr(), 16);
+  other.autodetect = false;
+
+  let other = other.snapshot();
+  let autodetect = (0..snapshot.len()).find(|&i| snapshot[i] != other[i]).unwrap();
+
+  let mut invalid = snapshot.clone();
+  invalid[autodetect] = 2;
+  assert_eq!(target.restore(&invalid), Err(SnapshotError::InvalidValue));
+
+  // Failed restores leave the parser untouched
+  assert_eq!(target.snapshot(), pristine);
+
+  target.restore(&snapshot).unwrap();
+  assert_eq!(target.snapshot(), snapshot);
+}
+
+#[test]
+fn snapshot_restore_chunk_data() {
+  let mut source = Parser::new();
+  source.active_events = EVENT_ACTIVE_ON_DATA;
+
+  let request = http(r"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\na\r\n0123456789\r\n0\r\n\r\n");
+  let split = request.find("01234").unwrap() + 5;
+
+  let mut received = body(&mut source, &request.as_bytes()[..split]);
+
+  assert_eq!(source.state, STATE_CHUNK_DATA);
+  assert_eq!(source.remaining_chunk_size, 5);
+
+  let mut target = Parser::new();
+  target.active_events = EVENT_ACTIVE_ON_DATA;
+  target.restore(&source.snapshot()).unwrap();
+
+  assert_eq!(target.remaining_chunk_size, 5);
+  assert!(target.has_chunked_transfer_encoding);
+
+  received.extend(body(&mut target, &request.as_bytes()[split..]));
+
+  assert_eq!(target.state, STATE_START);
+  assert_eq!(received, b"0123456789");
+}
+
+#[test]
+fn snapshot_restore_custom_error() {
+  let mut parser = Parser::new();
+  parser.parse(b"GET / HTTP/1.1\r\n".as_ptr(), 16);
+  parser.fail(200, "Custom error");
+
+  let snapshot = parser.snapshot();
+  let mut target = Parser::new();
+  target.restore(&snapshot).unwrap();
+
+  assert_eq!(target.state, STATE_ERROR);
+  assert_eq!(target.error_code, 200);
+  assert_eq!(target.error_description_str(), "Custom error");
+  assert_eq!(target.snapshot(), snapshot);
+}
@@ -0,0 +1,165 @@
+mod helpers;
+
+use milo_parser::head::HeaderRef;
+use milo_parser::{BodyKind, Errors, MessageStream, Parser, STATE_TUNNEL, StreamedMessage};
+
+use crate::helpers::http;
+
+fn collect<'a>(stream: &mut MessageStream<'a>) -> Vec<StreamedMessage<'a>> { stream.collect::<Result<_, _>>().unwrap() }
+
+#[test]
+fn stream_pipelined_requests() {
+  let input = http(
+    r#"
+      \r\n
+      POST /first HTTP/1.1\r\n
+      Content-Length: 3\r\n
+      \r\n
+      abc
+      \r\n
+      POST /second HTTP/1.1\r\n
+      Transfer-Encoding: chunked\r\n
+      Trailer: x-checksum\r\n
+      \r\n
+      2\r\n
+      de\r\n
+      1\r\n
+      f\r\n
+      0\r\n
+      x-checksum: 123\r\n
+      \r\n
+    "#,
+  );
+
+  let mut stream = MessageStream::new(input.as_bytes());
+  let messages = collect(&mut stream);
+
+  assert_eq!(messages.len(), 2);
+
+  let first = &messages[0];
+  assert!(first.is_request);
+  assert_eq!(first.range, 2..48);
+  assert_eq!(first.head, b"POST /first HTTP/1.1\r\nContent-Length: 3\r\n\r\n");
+  assert_eq!(first.metadata.body_kind, BodyKind::ContentLength);
+  assert_eq!(first.body, [b"abc"]);
+
+  let second = &messages[1];
+  assert_eq!(&input[second.range.clone()], &input[50..]);
+  assert!(second.head.starts_with(b"POST /second"));
+  assert!(second.metadata.has_trailers);
+  assert_eq!(second.body, [&b"de"[..], b"f"]);
+  assert_eq!(
+    second.trailers,
+    [HeaderRef {
+      name: "x-checksum",
+      value: b"123"
+    }]
+  );
+
+  assert!(stream.remaining().is_empty());
+}
+
+#[test]
+fn stream_stops_after_connection_close() {
+  let input = http(
+    r#"
+      HTTP/1.1 200 OK\r\n
+      Content-Length: 2\r\n
+      Connection: close\r\n
+      \r\n
+      ok
+      HTTP/1.1 200 OK\r\n
+      \r\n
+    "#,
+  );
+
+  let mut stream = MessageStream::new(input.as_bytes());
+  let messages = collect(&mut stream);
+
+  assert_eq!(messages.len(), 1);
+  assert!(!messages[0].is_request);
+  assert!(!messages[0].metadata.should_keep_alive);
+  assert_eq!(stream.remaining(), b"HTTP/1.1 200 OK\r\n\r\n");
+}
+
+#[test]
+fn stream_stops_after_connect_request() {
+  let input = b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n\x16\x03\x01";
+
+  let mut stream = MessageStream::new(input);
+  let messages = collect(&mut stream);
+
+  assert_eq!(messages.len(), 1);
+  assert!(messages[0].is_request);
+  assert_eq!(messages[0].range, 0..input.len() - 3);
+  assert!(messages[0].body.is_empty());
+  assert_eq!(stream.parser().state, STATE_TUNNEL);
+  assert_eq!(stream.remaining(), b"\x16\x03\x01");
+}
+
+#[test]
+fn stream_stops_after_upgrade_response() {
+  let input = http(
+    r#"
+      HTTP/1.1 101 Switching Protocols\r\n
+      Connection: Upgrade\r\n
+      Upgrade: websocket\r\n
+      \r\n
+      frames
+    "#,
+  );
+
+  let mut stream = MessageStream::new(input.as_bytes());
+  let messages = collect(&mut stream);
+
+  assert_eq!(messages.len(), 1);
+  assert!(!messages[0].is_request);
+  assert_eq!(messages[0].range, 0..input.len() - 6);
+  assert!(messages[0].metadata.should_upgrade);
+  assert_eq!(stream.parser().state, STATE_TUNNEL);
+  assert_eq!(stream.remaining(), b"frames");
+}
+
+#[test]
+fn stream_completes_body_at_end_of_buffer() {
+  let input = b"HTTP/1.1 200 OK\r\n\r\nuntil the end";
+
+  let mut stream = MessageStream::new(input);
+  let messages = collect(&mut stream);
+
+  assert_eq!(messages.len(), 1);
+  assert_eq!(messages[0].range, 0..input.len());
+  assert_eq!(messages[0].body, [b"until the end"]);
+}
+
+#[test]
+fn stream_errors() {
+  let input = b"GET / HTTP/1.1\r\n\r\nGET /truncated HTTP/1.1\r\nHost: loc";
+
+  let mut stream = MessageStream::new(input);
+
+  assert!(stream.next().unwrap().is_ok());
+  assert_eq!(stream.next().unwrap().unwrap_err().code, Errors::UNEXPECTED_EOF);
+  assert!(stream.next().is_none());
+  assert_eq!(stream.remaining(), b"GET /truncated HTTP/1.1\r\nHost: loc");
+
+  let mut stream = MessageStream::with_parser(b"GET / HTTP/1.1\r\nHo st: value\r\n\r\n", Parser::new());
+  let error = stream.next().unwrap().unwrap_err();
+
+  assert_eq!(error.code, Errors::UNEXPECTED_CHARACTER);
+  assert_eq!(error.offset, 16);
+  assert!(stream.next().is_none());
+}
+
+#[test]
+fn stream_ignores_managed_unconsumed() {
+  let mut parser = Parser::new();
+  parser.manage_unconsumed = true;
+
+  let mut stream = MessageStream::with_parser(b"GET / HTTP/1.1\r\n\r\nGET /next HTTP/1.1\r\nHo", parser);
+  assert!(!stream.parser().manage_unconsumed);
+
+  assert!(stream.next().unwrap().is_ok());
+  assert_eq!(stream.next().unwrap().unwrap_err().code, Errors::UNEXPECTED_EOF);
+  assert_eq!(stream.remaining(), b"GET /next HTTP/1.1\r\nHo");
+}
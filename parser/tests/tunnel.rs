@@ -0,0 +1,204 @@
+mod helpers;
+
+use std::cell::RefCell;
+
+use milo_parser::{
+  EVENT_ACTIVE_ON_CONNECT, EVENT_ACTIVE_ON_METHOD, EVENT_ACTIVE_ON_TUNNEL_DATA, EVENT_ACTIVE_ON_UPGRADE,
+  EVENT_ACTIVE_ON_URL, Errors, Event, Parser, ParserConfig, STATE_START, STATE_TUNNEL,
+};
+
+use crate::helpers::http;
+
+fn tunnel_parser() -> Parser {
+  let mut parser = Parser::new();
+  parser.emit_tunnel_data = true;
+  parser.active_events = EVENT_ACTIVE_ON_CONNECT | EVENT_ACTIVE_ON_UPGRADE | EVENT_ACTIVE_ON_TUNNEL_DATA;
+  parser
+}
+
+fn parse(parser: &mut Parser, input: &str) -> Vec<String> {
+  parser
+    .parse_slice(input.as_bytes())
+    .map(|event| {
+      match event {
+        Event::Range { event, data, .. } => format!("{:?}: {}", event, String::from_utf8_lossy(data)),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect()
+}
+
+#[test]
+fn tunnel_data_disabled_by_default() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_TUNNEL_DATA;
+
+  let input = "CONNECT example.com:443 HTTP/1.1\r\n\r\nabc";
+  let consumed = parser.parse(input.as_ptr(), input.len());
+
+  assert_eq!(consumed, input.len() - 3);
+  assert_eq!(parser.state, STATE_TUNNEL);
+  assert_eq!(parser.tunnel_bytes, 0);
+}
+
+#[test]
+fn tunnel_data_connect() {
+  let mut parser = tunnel_parser();
+
+  let input = "CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n\x16\x03\x01";
+  assert_eq!(parse(&mut parser, input), ["CONNECT: ", "TUNNEL_DATA: \x16\x03\x01"]);
+  assert_eq!(parser.position, input.len());
+  assert_eq!(parser.tunnel_bytes, 3);
+
+  // The parser keeps consuming in later invocations
+  assert_eq!(
+    parse(&mut parser, "GET / HTTP/1.1\r\n\r\n"),
+    ["TUNNEL_DATA: GET / HTTP/1.1\r\n\r\n"]
+  );
+  assert_eq!(parser.tunnel_bytes, 21);
+  assert_eq!(parser.state, STATE_TUNNEL);
+
+  assert!(parse(&mut parser, "").is_empty());
+  assert_eq!(parser.tunnel_bytes, 21);
+}
+
+#[test]
+fn tunnel_data_upgrade() {
+  let mut parser = tunnel_parser();
+
+  let input = http(
+    r#"
+      HTTP/1.1 101 Switching Protocols\r\n
+      Connection: upgrade\r\n
+      Upgrade: websocket\r\n
+      \r\n
+      frame
+    "#,
+  );
+
+  assert_eq!(parse(&mut parser, &input), ["UPGRADE: ", "TUNNEL_DATA: frame"]);
+  assert_eq!(parser.tunnel_bytes, 5);
+
+  // A new message starts counting again
+  parser.reset(false);
+  assert_eq!(parser.tunnel_bytes, 0);
+  assert!(parser.emit_tunnel_data);
+}
+
+thread_local! {
+  static RECEIVED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+fn on_tunnel_data(_parser: &mut Parser, _at: usize, len: usize) {
+  RECEIVED.with(|received| received.borrow_mut().push(len as u64));
+}
+
+#[test]
+fn tunnel_data_callbacks() {
+  let config = ParserConfig::builder().emit_tunnel_data(true).build().unwrap();
+  let mut parser = Parser::with_config(config).unwrap();
+  parser.callbacks.on_tunnel_data = on_tunnel_data;
+  parser.active_callbacks = EVENT_ACTIVE_ON_TUNNEL_DATA;
+
+  for input in ["CONNECT example.com:443 HTTP/1.1\r\n\r\nabc", "defgh"] {
+    let consumed = parser.parse(input.as_ptr(), input.len());
+    assert_eq!(consumed, input.len());
+  }
+
+  assert_eq!(RECEIVED.take(), [3, 5]);
+  assert_eq!(parser.tunnel_bytes, 8);
+}
+
+#[test]
+fn tunnel_cancel() {
+  let mut parser = Parser::new();
+  parser.manage_unconsumed = true;
+  parser.active_events = EVENT_ACTIVE_ON_METHOD | EVENT_ACTIVE_ON_URL;
+
+  let input = http(
+    r#"
+      GET /chat HTTP/1.1\r\n
+      Connection: upgrade\r\n
+      Upgrade: websocket\r\n
+      \r\n
+      GET /next HTTP/1.1\r\n
+      \r\n
+    "#,
+  );
+
+  // The data after the upgrade is buffered
+  assert_eq!(parse(&mut parser, &input), ["METHOD: GET", "URL: /chat"]);
+  assert_eq!(parser.state, STATE_TUNNEL);
+  assert_eq!(parser.unconsumed_len, 22);
+
+  // The server refused the upgrade, the buffered data is a new request
+  let consumed = parser.cancel_tunnel();
+  let buffered = &input.as_bytes()[input.len() - 22..];
+  let events: Vec<_> = parser
+    .iter_events(buffered)
+    .map(|event| {
+      match event {
+        Event::Range { event, data, .. } => format!("{:?}: {}", event, String::from_utf8_lossy(data)),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect();
+
+  assert_eq!(consumed, 22);
+  assert_eq!(events, ["METHOD: GET", "URL: /next"]);
+  assert_eq!(parser.state, STATE_START);
+  assert!(!parser.has_upgrade);
+  assert_eq!(parser.unconsumed_len, 0);
+  assert_eq!(parser.parsed, input.len() as u64);
+}
+
+#[test]
+fn tunnel_cancel_connect() {
+  let mut parser = tunnel_parser();
+
+  let input = "CONNECT example.com:443 HTTP/1.1\r\n\r\n";
+  assert_eq!(parse(&mut parser, input), ["CONNECT: "]);
+  assert!(parser.is_connect);
+
+  // Without managed unconsumed data, the caller passes the data again
+  assert_eq!(parser.cancel_tunnel(), 0);
+  assert_eq!(parser.state, STATE_START);
+  assert!(!parser.is_connect);
+  assert!(parser.emit_tunnel_data);
+
+  let input = "GET / HTTP/1.1\r\n\r\n";
+  assert!(parse(&mut parser, input).is_empty());
+  assert_eq!(parser.position, input.len());
+  assert_ne!(parser.state, STATE_TUNNEL);
+}
+
+#[test]
+fn tunnel_cancel_invalid_state() {
+  let mut parser = Parser::new();
+
+  assert_eq!(parser.cancel_tunnel(), 0);
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::UNEXPECTED_STATE);
+  assert_eq!(error.description, "Invalid state");
+}
+
+#[test]
+fn tunnel_cancel_after_tunnel_data() {
+  let mut parser = tunnel_parser();
+  parser.manage_unconsumed = true;
+
+  let input = "CONNECT example.com:443 HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n";
+  assert_eq!(
+    parse(&mut parser, input),
+    ["CONNECT: ", "TUNNEL_DATA: GET / HTTP/1.1\r\n\r\n"]
+  );
+  assert_eq!(parser.tunnel_bytes, 18);
+
+  // The data was emitted as tunnel data, so it cannot be parsed again
+  assert_eq!(parser.cancel_tunnel(), 0);
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::UNEXPECTED_STATE);
+  assert_eq!(error.description, "Cannot cancel a tunnel which consumed data");
+}
@@ -0,0 +1,90 @@
+mod helpers;
+
+use milo_parser::{
+  ERROR_UNCONSUMED_DATA_TOO_LARGE, EVENT_ACTIVE_ON_HEADER_NAME, EVENT_ACTIVE_ON_HEADER_VALUE, EVENT_ACTIVE_ON_URL,
+  Event, Events, Parser, STATE_ERROR,
+};
+
+use crate::helpers::http;
+
+fn trickle(parser: &mut Parser, message: &str, step: usize) {
+  for chunk in message.as_bytes().chunks(step) {
+    parser.parse(chunk.as_ptr(), chunk.len());
+    assert_ne!(parser.state, STATE_ERROR);
+  }
+}
+
+#[test]
+fn unconsumed_carry_buffer_is_reused() {
+  let mut parser = Parser::new();
+  parser.manage_unconsumed = true;
+
+  let message = http(r"GET /path HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n");
+
+  trickle(&mut parser, &message, 3);
+  assert_eq!(parser.parsed, message.len() as u64);
+
+  let allocations = parser.unconsumed_allocations;
+  assert!(allocations > 0);
+
+  // Steady state parsing must not allocate anymore
+  for _ in 0..10 {
+    trickle(&mut parser, &message, 3);
+  }
+
+  parser.reset(false);
+  trickle(&mut parser, &message, 3);
+
+  assert_eq!(parser.unconsumed_allocations, allocations);
+  assert_eq!(parser.parsed, message.len() as u64);
+}
+
+#[test]
+fn unconsumed_parse_slice_resolves_carried_data() {
+  let mut parser = Parser::new();
+  parser.manage_unconsumed = true;
+  parser.active_events = EVENT_ACTIVE_ON_URL | EVENT_ACTIVE_ON_HEADER_NAME | EVENT_ACTIVE_ON_HEADER_VALUE;
+
+  let message = http(r"GET /path HTTP/1.1\r\nHost: localhost\r\n\r\n");
+  let mut ranges = Vec::new();
+
+  for chunk in message.as_bytes().chunks(4) {
+    for event in parser.parse_slice(chunk) {
+      match event {
+        Event::Range { event, data, .. } => ranges.push((event, String::from_utf8(data.to_vec()).unwrap())),
+        Event::HeaderName { data, .. } => ranges.push((Events::HEADER_NAME, String::from_utf8(data.to_vec()).unwrap())),
+        _ => (),
+      }
+    }
+  }
+
+  assert_eq!(
+    ranges,
+    [
+      (Events::URL, "/path".into()),
+      (Events::HEADER_NAME, "Host".into()),
+      (Events::HEADER_VALUE, "localhost".into()),
+    ]
+  );
+}
+
+#[test]
+fn unconsumed_data_length_is_limited() {
+  let mut parser = Parser::new();
+  parser.manage_unconsumed = true;
+  parser.max_unconsumed_length = 8;
+
+  let message = http(r"GET /a-very-long-path HTTP/1.1\r\n\r\n");
+
+  for chunk in message.as_bytes().chunks(4) {
+    parser.parse(chunk.as_ptr(), chunk.len());
+
+    if parser.state == STATE_ERROR {
+      break;
+    }
+  }
+
+  assert_eq!(parser.state, STATE_ERROR);
+  assert_eq!(parser.error_code, ERROR_UNCONSUMED_DATA_TOO_LARGE);
+  assert_eq!(parser.unconsumed_len, 0);
+}
@@ -1,7 +1,22 @@
 mod helpers;
 
 use helpers::{create_parser, http, parse};
-use milo_parser::STATE_TUNNEL;
+use milo_parser::{
+  EVENT_ACTIVE_ON_HEADER_VALUE, EVENT_ACTIVE_ON_UPGRADE, EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME,
+  EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_VERSION, Errors, Event, Events, Handler, Parser, STATE_TUNNEL,
+};
+
+fn upgrade_events(parser: &mut Parser, input: &str) -> Vec<String> {
+  parser
+    .parse_slice(input.as_bytes())
+    .map(|event| {
+      match event {
+        Event::Range { event, data, .. } => format!("{:?}: {}", event, String::from_utf8_lossy(data)),
+        event => panic!("Unexpected event {event:?}"),
+      }
+    })
+    .collect()
+}
 
 #[test]
 fn upgrade_connect_request() {
@@ -92,3 +107,121 @@ fn upgrade_http_101() {
   assert_eq!(consumed2, 0);
   assert_eq!(parser.state, STATE_TUNNEL);
 }
+
+#[test]
+fn upgrade_protocols_events() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HEADER_VALUE
+    | EVENT_ACTIVE_ON_UPGRADE
+    | EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME
+    | EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_VERSION;
+
+  let input = http(
+    r#"
+      HTTP/1.1 101 Switching Protocols\r\n
+      Connection: upgrade\r\n
+      Upgrade: websocket,  h2c , TLS/1.2,\r\n
+      \r\n
+    "#,
+  );
+
+  // Protocols are reported after the header value and before the upgrade
+  assert_eq!(
+    upgrade_events(&mut parser, &input),
+    [
+      "HEADER_VALUE: upgrade",
+      "HEADER_VALUE: websocket,  h2c , TLS/1.2,",
+      "UPGRADE_PROTOCOL_NAME: websocket",
+      "UPGRADE_PROTOCOL_NAME: h2c",
+      "UPGRADE_PROTOCOL_NAME: TLS",
+      "UPGRADE_PROTOCOL_VERSION: 1.2",
+      "UPGRADE: ",
+    ]
+  );
+  assert_eq!(parser.state, STATE_TUNNEL);
+}
+
+#[derive(Default)]
+struct Recorder {
+  protocols: Vec<String>,
+}
+
+impl Handler for Recorder {
+  fn on_upgrade_protocol_name(&mut self, _parser: &mut Parser, data: &[u8]) {
+    self.protocols.push(String::from_utf8_lossy(data).into_owned());
+  }
+
+  fn on_upgrade_protocol_version(&mut self, _parser: &mut Parser, data: &[u8]) {
+    if let Some(protocol) = self.protocols.last_mut() {
+      protocol.push('/');
+      protocol.push_str(&String::from_utf8_lossy(data));
+    }
+  }
+}
+
+#[test]
+fn upgrade_protocols_handler() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME | EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_VERSION;
+
+  let mut recorder = Recorder::default();
+  parser.parse_with(
+    b"GET / HTTP/1.1\r\nConnection: upgrade\r\nUpgrade: HTTP/2.0, websocket\r\n\r\n",
+    &mut recorder,
+  );
+
+  assert_eq!(recorder.protocols, ["HTTP/2.0", "websocket"]);
+}
+
+#[test]
+fn upgrade_too_many_protocols() {
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME;
+
+  let input = format!(
+    "GET / HTTP/1.1\r\nConnection: upgrade\r\nUpgrade: {}\r\n\r\n",
+    ["websocket"; 9].join(", ")
+  );
+  parser.parse(input.as_ptr(), input.len());
+
+  let error = parser.error().unwrap();
+  assert_eq!(error.code, Errors::TOO_MANY_UPGRADE_PROTOCOLS);
+  assert_eq!(error.description, "Too many protocols in Upgrade header");
+
+  // The limit is inclusive
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_UPGRADE_PROTOCOL_NAME;
+
+  let input = format!(
+    "GET / HTTP/1.1\r\nConnection: upgrade\r\nUpgrade: {}\r\n\r\n",
+    ["websocket"; 8].join(", ")
+  );
+  let events = parser
+    .parse_slice(input.as_bytes())
+    .filter(|event| {
+      matches!(
+        event,
+        Event::Range {
+          event: Events::UPGRADE_PROTOCOL_NAME,
+          ..
+        }
+      )
+    })
+    .count();
+
+  assert_eq!(events, 8);
+  assert_eq!(parser.state, STATE_TUNNEL);
+
+  // Without protocol events there is no limit
+  let mut parser = Parser::new();
+  parser.active_events = EVENT_ACTIVE_ON_HEADER_VALUE;
+
+  let input = format!(
+    "GET / HTTP/1.1\r\nConnection: upgrade\r\nUpgrade: {}\r\n\r\n",
+    ["websocket"; 32].join(", ")
+  );
+  parser.parse(input.as_ptr(), input.len());
+
+  assert!(parser.error().is_none());
+  assert_eq!(parser.state, STATE_TUNNEL);
+}
@@ -49,12 +49,14 @@ async function readParserFields () {
 }
 
 export async function getBuildInfo () {
-  const [version, methods, errors, callbacks, states, parserFields] = await Promise.all([
+  const [version, methods, headers, errors, callbacks, states, lenient, parserFields] = await Promise.all([
     readVersion(),
     readYamlList('methods'),
+    readYamlList('headers'),
     readYamlList('errors'),
     readYamlList('callbacks'),
     readYamlList('states'),
+    readYamlList('lenient'),
     readParserFields()
   ])
   const constants = {}
@@ -63,6 +65,10 @@ export async function getBuildInfo () {
     constants[`METHOD_${method.replaceAll('-', '_')}`] = i
   }
 
+  for (const [i, header] of headers.entries()) {
+    constants[`HEADER_${header.toUpperCase().replaceAll('-', '_')}`] = i
+  }
+
   for (const [i, callback] of callbacks.entries()) {
     constants[`CALLBACK_${callback.toUpperCase()}`] = i
   }
@@ -92,6 +98,23 @@ export async function getBuildInfo () {
     constants[`STATE_${state.toUpperCase()}`] = i
   }
 
+  let lenientAll = 0
+  constants.LENIENT_NONE = 0
+  for (const [i, flag] of lenient.entries()) {
+    const bit = 1 << i
+    constants[`LENIENT_${flag.toUpperCase()}`] = bit
+    lenientAll |= bit
+  }
+  constants.LENIENT_ALL = lenientAll
+
+  constants.MESSAGE_TYPE_AUTODETECT = 0
+  constants.MESSAGE_TYPE_REQUEST = 1
+  constants.MESSAGE_TYPE_RESPONSE = 2
+
+  constants.PROTOCOL_HTTP = 0
+  constants.PROTOCOL_RTSP = 1
+  constants.PROTOCOL_ICE = 2
+
   Object.assign(constants, parserFields)
 
   return { version, constants }
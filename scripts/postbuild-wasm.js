@@ -8,6 +8,7 @@ import { getBuildInfo } from './buildinfo.js'
 const enums = {
   ERROR: 'Errors',
   METHOD: 'Methods',
+  HEADER: 'Headers',
   CALLBACK: 'Callbacks',
   CALLBACK_ACTIVE: 'CallbackActives',
   EVENT: 'Events',
@@ -25,26 +26,42 @@ const getters = {
   getMaxStartLineLength: ['number', 'get_max_start_line_length'],
   getMaxHeaderLength: ['number', 'get_max_header_length'],
   getMaxBodyPayload: ['bigint', 'get_max_body_payload'],
+  getMaxUnconsumedLength: ['number', 'get_max_unconsumed_length'],
+  getLenientFlags: ['bigint', 'get_lenient_flags'],
+  getProtocol: ['number', 'get_protocol'],
+  getUnconsumedAllocations: ['bigint', 'get_unconsumed_allocations'],
   shouldContinueWithoutData: ['bool', 'should_continue_without_data'],
   isConnect: ['bool', 'is_connect'],
   isDebug: ['bool', 'is_debug'],
   shouldSkipBody: ['bool', 'should_skip_body'],
+  shouldExpectConnectResponse: ['bool', 'should_expect_connect_response'],
+  shouldAllowHttp10: ['bool', 'should_allow_http10'],
+  shouldEmitTunnelData: ['bool', 'should_emit_tunnel_data'],
   getState: ['number', 'get_state'],
   getPosition: ['number', 'get_position'],
   getParsed: ['bigint', 'get_parsed'],
   getErrorCode: ['number', 'get_error_code'],
   getMethod: ['number', 'get_method'],
+  getKnownHeader: ['number', 'get_known_header'],
+  getHttp2SettingIdentifier: ['number', 'get_http2_setting_identifier'],
+  getHttp2SettingValue: ['number', 'get_http2_setting_value'],
+  getVersionMinor: ['number', 'get_version_minor'],
   getStatus: ['number', 'get_status'],
   hasConnectionClose: ['bool', 'has_connection_close'],
+  hasConnectionKeepAlive: ['bool', 'has_connection_keep_alive'],
   hasConnectionUpgrade: ['bool', 'has_connection_upgrade'],
   getContentLength: ['bigint', 'get_content_length'],
   getChunkSize: ['bigint', 'get_chunk_size'],
   getRemainingContentLength: ['bigint', 'get_remaining_content_length'],
   getRemainingChunkSize: ['bigint', 'get_remaining_chunk_size'],
+  getTunnelBytes: ['bigint', 'get_tunnel_bytes'],
   hasContentLength: ['bool', 'has_content_length'],
   hasTransferEncoding: ['bool', 'has_transfer_encoding'],
   hasChunkedTransferEncoding: ['bool', 'has_chunked_transfer_encoding'],
   hasUpgrade: ['bool', 'has_upgrade'],
+  hasUpgradeH2c: ['bool', 'has_upgrade_h2c'],
+  hasHttp2Settings: ['bool', 'has_http2_settings'],
+  hasConnectionHttp2Settings: ['bool', 'has_connection_http2_settings'],
   hasTrailers: ['bool', 'has_trailers'],
   getErrorDescription: ['string', 'get_error_description_raw']
 }
@@ -60,9 +77,28 @@ const setters = {
   setMaxStartLineLength: 'set_max_start_line_length',
   setMaxHeaderLength: 'set_max_header_length',
   setMaxBodyPayload: 'set_max_body_payload',
+  setMaxUnconsumedLength: 'set_max_unconsumed_length',
+  setLenientFlags: 'set_lenient_flags',
+  setProtocol: 'set_protocol',
   setShouldSkipBody: 'set_should_skip_body',
+  setShouldExpectConnectResponse: 'set_should_expect_connect_response',
+  setShouldAllowHttp10: 'set_should_allow_http10',
+  setShouldEmitTunnelData: 'set_should_emit_tunnel_data',
   setActiveCallbacks: 'set_active_callbacks',
-  setActiveEvents: 'set_active_events'
+  setActiveEvents: 'set_active_events',
+  setConfigMaxStartLineLength: 'set_config_max_start_line_length',
+  setConfigMaxHeaderLength: 'set_config_max_header_length',
+  setConfigMaxBodyPayload: 'set_config_max_body_payload',
+  setConfigMaxUnconsumedLength: 'set_config_max_unconsumed_length',
+  setConfigLenientFlags: 'set_config_lenient_flags',
+  setConfigMessageType: 'set_config_message_type',
+  setConfigProtocol: 'set_config_protocol',
+  setConfigSuspendAfterHeaders: 'set_config_suspend_after_headers',
+  setConfigManageUnconsumed: 'set_config_manage_unconsumed',
+  setConfigSkipBody: 'set_config_skip_body',
+  setConfigDebug: 'set_config_debug',
+  setConfigAllowHttp10: 'set_config_allow_http10',
+  setConfigEmitTunnelData: 'set_config_emit_tunnel_data'
 }
 
 function getCallbacks (constants) {